@@ -1,6 +1,10 @@
 use ec_snark::common::{FromBytes, IntoBytes, Random};
+use ec_snark::protocol::elliptic_curves::CurveBackend;
+use ec_snark::protocol::rotation::{prove_rotation, rotation_context, RotationProof};
 use ec_snark::protocol::GroupParams;
 use ec_snark::protocol::Protocol;
+use ec_snark::secret::SecretScalar;
+use rand_core::OsRng;
 use std::error::Error;
 use tonic::codegen::StdError;
 use tonic::transport::Channel;
@@ -11,6 +15,7 @@ pub mod ec_auth {
 
 use ec_auth::{
     authentication_client::AuthenticationClient, AnswerRequest, ChallengeRequest, RegisterRequest,
+    RotateRequest, SaltRequest, ServiceIdRequest,
 };
 
 pub struct AuthClientLib {
@@ -32,12 +37,36 @@ impl AuthClientLib {
         user: String,
         y1: Vec<u8>,
         y2: Vec<u8>,
+        salt: Vec<u8>,
     ) -> Result<(), tonic::Status> {
-        let request = RegisterRequest { user, y1, y2 };
+        let request = RegisterRequest { user, y1, y2, salt };
         self.client.register_user(request).await?;
         Ok(())
     }
 
+    /// Returns the salt stored for `user` and the number of rotations of the user's
+    /// credentials, or `None` if the user is not registered yet.
+    pub async fn get_salt(
+        &mut self,
+        user: String,
+    ) -> Result<Option<(Vec<u8>, u64)>, tonic::Status> {
+        let request = SaltRequest { user };
+        match self.client.get_salt(request).await {
+            Ok(response) => {
+                let response = response.into_inner();
+                Ok(Some((response.salt, response.rotations)))
+            }
+            Err(status) if status.code() == tonic::Code::NotFound => Ok(None),
+            Err(status) => Err(status),
+        }
+    }
+
+    /// Returns the identity the server announces, which the secret is derived for.
+    pub async fn get_service_id(&mut self) -> Result<String, tonic::Status> {
+        let response = self.client.get_service_id(ServiceIdRequest {}).await?;
+        Ok(response.into_inner().service_id)
+    }
+
     pub async fn create_challenge(
         &mut self,
         user: String,
@@ -55,12 +84,33 @@ impl AuthClientLib {
         let response = self.client.verify(request).await?;
         Ok(response.into_inner().session_id)
     }
+
+    pub async fn rotate_credentials(
+        &mut self,
+        user: String,
+        y1: Vec<u8>,
+        y2: Vec<u8>,
+        salt: Vec<u8>,
+        proof: Vec<u8>,
+    ) -> Result<(), tonic::Status> {
+        let request = RotateRequest {
+            user,
+            y1,
+            y2,
+            salt,
+            proof,
+        };
+        self.client.rotate_credentials(request).await?;
+        Ok(())
+    }
 }
 
 pub async fn run_protocol<T, P, S>(
     params: &GroupParams<P>,
     x: &T::Secret,
-    user: &String,
+    salt: &[u8],
+    registered: bool,
+    user: &str,
     client: &mut AuthClientLib,
 ) -> Result<(), Box<dyn Error>>
 where
@@ -73,26 +123,62 @@ where
     P: FromBytes<P> + IntoBytes<P> + Random<P>,
     S: FromBytes<S> + IntoBytes<S> + Random<S>,
 {
-    let ((y1, y2, r1, r2), k) = T::commitment(params, x);
+    let ((y1, y2, r1, r2), k) = T::commitment(params, x)?;
 
-    client
-        .register_user(user.clone(), P::to(&y1), P::to(&y2))
-        .await?;
+    if !registered {
+        client
+            .register_user(user.to_string(), P::to(&y1), P::to(&y2), salt.to_vec())
+            .await?;
+    }
 
     let (c, auth_id) = client
-        .create_challenge(user.clone(), P::to(&r1), P::to(&r2))
+        .create_challenge(user.to_string(), P::to(&r1), P::to(&r2))
         .await?;
 
     let challenge = S::from(&c)?;
 
-    let s = T::challenge_response(&params, &k, &challenge, &x);
+    let s = T::challenge_response(params, k, &challenge, x);
 
     let session_id = client.verify(auth_id, S::to(&s)).await?;
 
     println!("Authentication successful!");
     println!("Session ID: {}", session_id);
 
-    T::verify(&params, &s, &challenge, &(y1, y2, r1, r2));
+    T::verify(params, &s, &challenge, &(y1, y2, r1, r2))?;
+
+    Ok(())
+}
+
+/// Replaces the registered credentials of `user` with those of the secret `new`.
+///
+/// The server only accepts the change with a proof that the caller knows `old`,
+/// bound to the number of `rotations` it has accepted for `user` so far.
+pub async fn rotate_credentials<G>(
+    params: &GroupParams<G>,
+    old: &SecretScalar<G::Scalar>,
+    new: &SecretScalar<G::Scalar>,
+    salt: &[u8],
+    rotations: u64,
+    user: &str,
+    client: &mut AuthClientLib,
+) -> Result<(), Box<dyn Error>>
+where
+    G: CurveBackend,
+    G::Scalar: IntoBytes<G::Scalar>,
+{
+    let context = rotation_context(user, salt, rotations);
+    let ((y1, y2), proof) = prove_rotation(params, &context, old, new, &mut OsRng)?;
+    client
+        .rotate_credentials(
+            user.to_string(),
+            G::to(&y1),
+            G::to(&y2),
+            salt.to_vec(),
+            RotationProof::to(&proof),
+        )
+        .await?;
+
+    println!("Credentials rotated!");
 
     Ok(())
 }
@@ -1,20 +1,24 @@
 pub mod logic;
 
-use sha2::{Digest, Sha512};
 use std::str::FromStr;
 use structopt::StructOpt;
 use strum::VariantNames;
 
-use ec_snark::common::{FromBytes, IntoBytes};
-
+use curve25519_dalek::ristretto::RistrettoPoint;
 use ec_snark::common::EllipticCurve;
-use ec_snark::common::Random;
+use ec_snark::common::{IntoBytes, Random};
+use ec_snark::kdf::{derive_child, derive_secret, random_salt, KdfParams, HARDENED};
 use ec_snark::protocol::{
-    elliptic_curves::pallas::PallasEllipticCurve, elliptic_curves::vesta::VestaEllipticCurve,
+    elliptic_curves::bls12381::{Bls12381EllipticCurve, Point as Bls12381Point},
+    elliptic_curves::pallas::PallasEllipticCurve,
+    elliptic_curves::ristretto::RistrettoEllipticCurve,
+    elliptic_curves::vesta::VestaEllipticCurve,
+    elliptic_curves::CurveBackend,
     GroupParams,
 };
-use logic::run_protocol;
+use ec_snark::secret::SecretScalar;
 use logic::AuthClientLib;
+use logic::{rotate_credentials, run_protocol};
 use pasta_curves::pallas::Point as PallasPoint;
 use pasta_curves::vesta::Point as VestaPoint;
 use std::error::Error;
@@ -35,22 +39,58 @@ struct Cli {
 
     #[structopt(short, long, possible_values = EllipticCurve::VARIANTS, default_value = "pallas")]
     curve: EllipticCurve,
+
+    /// Identity of the server the secret is derived for. Defaults to the one the
+    /// server announces; set it to refuse to log in anywhere else.
+    #[structopt(long)]
+    service: Option<String>,
+
+    /// Password to rotate the registered credentials to after logging in.
+    #[structopt(long)]
+    new_secret: Option<String>,
 }
 
-fn hash_or_randomize_secret<T: FromBytes<T> + IntoBytes<T> + Random<T>>(
+/// Derives the secret for `service` from the password, or draws a random one.
+///
+/// The password yields a master secret, from which every service gets its own
+/// hardened child, so registrations at different servers cannot be linked.
+fn derive_or_randomize_secret<G>(
     secret: Option<&String>,
-) -> T {
+    salt: &[u8],
+    service: &str,
+) -> Result<SecretScalar<G::Scalar>, ec_snark::Error>
+where
+    G: CurveBackend,
+    G::Scalar: Random<G::Scalar>,
+{
     match secret {
         Some(s) => {
-            let mut hasher = Sha512::new();
-            hasher.update(s);
-            let result = hasher.finalize();
-            T::from(&result).expect("Hash convertion error")
+            let master = derive_secret::<G>(s.as_bytes(), salt, &KdfParams::default())?;
+            derive_child::<G>(&master, service, HARDENED)
         }
-        None => T::random().expect("Random value generation error"),
+        None => Ok(SecretScalar::new(G::Scalar::random()?)),
     }
 }
 
+/// Rotates the credentials of `user` to a secret derived from `new_secret` with a fresh salt.
+async fn rotate<G>(
+    params: &GroupParams<G>,
+    old: &SecretScalar<G::Scalar>,
+    new_secret: &String,
+    service: &str,
+    rotations: u64,
+    user: &str,
+    client: &mut AuthClientLib,
+) -> Result<(), Box<dyn Error>>
+where
+    G: CurveBackend,
+    G::Scalar: Random<G::Scalar> + IntoBytes<G::Scalar>,
+{
+    let salt = random_salt().to_vec();
+    let new = derive_or_randomize_secret::<G>(Some(new_secret), &salt, service)?;
+    rotate_credentials(params, old, &new, &salt, rotations, user, client).await
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     let opt = Cli::from_args();
@@ -62,29 +102,131 @@ async fn main() -> Result<(), Box<dyn Error>> {
     println!("      user: {}", opt.user);
 
     let mut client = AuthClientLib::connect(format!("http://{}:{}", opt.host, opt.port)).await?;
+    // The secret is derived for the server's own identity, not for the address it was
+    // reached at, so every route to the same server gives the same secret.
+    let announced = client.get_service_id().await?;
+    let service = match &opt.service {
+        Some(service) if *service != announced => {
+            return Err(format!("Server identifies as {}, expected {}", announced, service).into())
+        }
+        _ => announced,
+    };
+    println!("      service: {}", service);
+    // A registered user derives the secret with the stored salt, a new one creates it.
+    let (salt, rotations, registered) = match client.get_salt(opt.user.clone()).await? {
+        Some((salt, rotations)) => (salt, rotations, true),
+        None => (random_salt().to_vec(), 0, false),
+    };
     match opt.curve {
         EllipticCurve::Pallas => {
-            let ec_params = GroupParams::<PallasPoint>::from_str(&opt.curve.to_string())
-                .map_err(|_| "Invalid group parameters value".to_string())?;
+            let ec_params = GroupParams::<PallasPoint>::from_str(&opt.curve.to_string())?;
+            ec_params.validate()?;
+            let x =
+                derive_or_randomize_secret::<PallasPoint>(opt.secret.as_ref(), &salt, &service)?;
             run_protocol::<PallasEllipticCurve, _, _>(
                 &ec_params,
-                &hash_or_randomize_secret(opt.secret.as_ref()),
+                &x,
+                &salt,
+                registered,
                 &opt.user,
                 &mut client,
             )
-            .await?
+            .await?;
+            if let Some(new_secret) = &opt.new_secret {
+                rotate(
+                    &ec_params,
+                    &x,
+                    new_secret,
+                    &service,
+                    rotations,
+                    &opt.user,
+                    &mut client,
+                )
+                .await?;
+            }
         }
 
         EllipticCurve::Vesta => {
-            let ec_params = GroupParams::<VestaPoint>::from_str(&opt.curve.to_string())
-                .map_err(|_| "Invalid group parameters value".to_string())?;
+            let ec_params = GroupParams::<VestaPoint>::from_str(&opt.curve.to_string())?;
+            ec_params.validate()?;
+            let x = derive_or_randomize_secret::<VestaPoint>(opt.secret.as_ref(), &salt, &service)?;
             run_protocol::<VestaEllipticCurve, _, _>(
                 &ec_params,
-                &hash_or_randomize_secret(opt.secret.as_ref()),
+                &x,
+                &salt,
+                registered,
+                &opt.user,
+                &mut client,
+            )
+            .await?;
+            if let Some(new_secret) = &opt.new_secret {
+                rotate(
+                    &ec_params,
+                    &x,
+                    new_secret,
+                    &service,
+                    rotations,
+                    &opt.user,
+                    &mut client,
+                )
+                .await?;
+            }
+        }
+
+        EllipticCurve::Ristretto => {
+            let ec_params = GroupParams::<RistrettoPoint>::from_str(&opt.curve.to_string())?;
+            ec_params.validate()?;
+            let x =
+                derive_or_randomize_secret::<RistrettoPoint>(opt.secret.as_ref(), &salt, &service)?;
+            run_protocol::<RistrettoEllipticCurve, _, _>(
+                &ec_params,
+                &x,
+                &salt,
+                registered,
+                &opt.user,
+                &mut client,
+            )
+            .await?;
+            if let Some(new_secret) = &opt.new_secret {
+                rotate(
+                    &ec_params,
+                    &x,
+                    new_secret,
+                    &service,
+                    rotations,
+                    &opt.user,
+                    &mut client,
+                )
+                .await?;
+            }
+        }
+
+        EllipticCurve::Bls12381 => {
+            let ec_params = GroupParams::<Bls12381Point>::from_str(&opt.curve.to_string())?;
+            ec_params.validate()?;
+            let x =
+                derive_or_randomize_secret::<Bls12381Point>(opt.secret.as_ref(), &salt, &service)?;
+            run_protocol::<Bls12381EllipticCurve, _, _>(
+                &ec_params,
+                &x,
+                &salt,
+                registered,
                 &opt.user,
                 &mut client,
             )
-            .await?
+            .await?;
+            if let Some(new_secret) = &opt.new_secret {
+                rotate(
+                    &ec_params,
+                    &x,
+                    new_secret,
+                    &service,
+                    rotations,
+                    &opt.user,
+                    &mut client,
+                )
+                .await?;
+            }
         }
     }
     Ok(())
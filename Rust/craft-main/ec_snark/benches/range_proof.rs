@@ -0,0 +1,35 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ec_snark::protocol::elliptic_curves::CurveBackend;
+use ec_snark::protocol::pedersen::Opening;
+use ec_snark::protocol::range_proof::{RangeProof, RangeProofGens};
+use pasta_curves::{pallas, vesta};
+use rand_core::OsRng;
+
+/// Benchmarks the verification of proofs for 1, 2, 4 and 8 values of 64 bits.
+fn verify_range_proofs<G: CurveBackend>(c: &mut Criterion, name: &str) {
+    let params = G::group_params();
+    let gens = RangeProofGens::<G>::new(64 * 8);
+    let mut group = c.benchmark_group(format!("{}/range_proof_verify", name));
+    for m in [1, 2, 4, 8] {
+        let openings: Vec<_> = (0..m)
+            .map(|v| Opening::random(G::Scalar::from(v), &mut OsRng))
+            .collect();
+        let (proof, commitments) =
+            RangeProof::prove(&params, &gens, b"bench", &openings, 64, &mut OsRng).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(m), &m, |b, _| {
+            b.iter(|| proof.verify(&params, &gens, b"bench", &commitments, 64))
+        });
+    }
+    group.finish();
+}
+
+fn pallas(c: &mut Criterion) {
+    verify_range_proofs::<pallas::Point>(c, "pallas");
+}
+
+fn vesta(c: &mut Criterion) {
+    verify_range_proofs::<vesta::Point>(c, "vesta");
+}
+
+criterion_group!(benches, pallas, vesta);
+criterion_main!(benches);
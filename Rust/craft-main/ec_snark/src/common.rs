@@ -1,11 +1,15 @@
-use std::error::Error;
+use crate::Error;
 
 /// An enumeration representing the types of elliptic curves.
-#[derive(PartialEq, Debug, strum::EnumString, strum::EnumVariantNames, strum::Display)]
+#[derive(
+    Copy, Clone, PartialEq, Debug, strum::EnumString, strum::EnumVariantNames, strum::Display,
+)]
 #[strum(serialize_all = "snake_case")]
 pub enum EllipticCurve {
     Pallas,
     Vesta,
+    Ristretto,
+    Bls12381,
 }
 
 /// Trait for converting types to and from byte representations.
@@ -16,7 +20,7 @@ pub trait IntoBytes<T> {
 /// Trait for converting types from byte representations.
 /// Similar to `std::convert::From`
 pub trait FromBytes<T> {
-    fn from(bytes: &[u8]) -> Result<T, Box<dyn Error>>
+    fn from(bytes: &[u8]) -> Result<T, Error>
     where
         Self: Sized;
 }
@@ -24,5 +28,89 @@ pub trait FromBytes<T> {
 /// Trait for generating random values of a given type.
 /// /// Similar to `std::convert::Into`
 pub trait Random<T> {
-    fn random() -> Result<T, Box<dyn Error>>;
+    fn random() -> Result<T, Error>;
+}
+
+/// Concatenates encoded values, prefixing each one with its length
+/// as a big-endian `u16`.
+///
+/// # Panics
+/// If a part is longer than `u16::MAX` bytes. Every encoding of the crate keeps its
+/// parts below that bound.
+pub fn encode_parts(parts: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(parts.iter().map(|p| p.len() + 2).sum());
+    for part in parts {
+        assert!(part.len() <= u16::MAX as usize, "Part too long to encode");
+        out.extend_from_slice(&(part.len() as u16).to_be_bytes());
+        out.extend_from_slice(part);
+    }
+    out
+}
+
+/// Splits bytes produced by [`encode_parts`] back into exactly `count` parts.
+pub fn decode_parts(bytes: &[u8], count: usize) -> Result<Vec<&[u8]>, Error> {
+    let mut parts = Vec::with_capacity(count);
+    let mut offset = 0;
+    for _ in 0..count {
+        if bytes.len() < offset + 2 {
+            return Err(Error::InvalidLength {
+                expected: offset + 2,
+                actual: bytes.len(),
+            });
+        }
+        let len = u16::from_be_bytes([bytes[offset], bytes[offset + 1]]) as usize;
+        let end = offset + 2 + len;
+        if bytes.len() < end {
+            return Err(Error::InvalidLength {
+                expected: end,
+                actual: bytes.len(),
+            });
+        }
+        parts.push(&bytes[offset + 2..end]);
+        offset = end;
+    }
+    if bytes.len() != offset {
+        return Err(Error::InvalidLength {
+            expected: offset,
+            actual: bytes.len(),
+        });
+    }
+    Ok(parts)
+}
+
+/// Returns the first part of bytes produced by [`encode_parts`], e.g. a count that
+/// determines how many parts to pass to [`decode_parts`].
+pub fn first_part(bytes: &[u8]) -> Result<&[u8], Error> {
+    let len = match bytes {
+        [hi, lo, ..] => u16::from_be_bytes([*hi, *lo]) as usize,
+        _ => {
+            return Err(Error::InvalidLength {
+                expected: 2,
+                actual: bytes.len(),
+            })
+        }
+    };
+    bytes.get(2..2 + len).ok_or(Error::InvalidLength {
+        expected: 2 + len,
+        actual: bytes.len(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn longest_part() {
+        let parts = vec![vec![7; u16::MAX as usize], vec![]];
+        let bytes = encode_parts(&parts);
+        assert_eq!(&bytes[..2], &[0xff, 0xff]);
+        assert_eq!(decode_parts(&bytes, 2).unwrap(), parts);
+    }
+
+    #[test]
+    #[should_panic(expected = "Part too long to encode")]
+    fn part_too_long() {
+        encode_parts(&[vec![0; u16::MAX as usize + 1]]);
+    }
 }
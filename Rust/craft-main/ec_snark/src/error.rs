@@ -0,0 +1,58 @@
+use std::fmt;
+
+/// Errors returned by the decoding and protocol entry points of the crate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The input does not have the length the encoding requires.
+    InvalidLength { expected: usize, actual: usize },
+    /// The input decodes to a valid value, but is not its canonical encoding.
+    NonCanonicalEncoding,
+    /// The input is the identity element where a non-trivial point is required.
+    IdentityPoint,
+    /// The input does not encode a point of the group.
+    OffCurvePoint,
+    /// The curve name is not known, or does not match the requested group.
+    UnknownCurve(String),
+    /// The key derivation function rejected its cost parameters.
+    InvalidKdfParams(String),
+    /// The witness does not satisfy the statement it is used for.
+    InvalidWitness,
+    /// The group parameters are unusable, e.g. because both generators are equal.
+    InvalidGroupParams(String),
+    /// The bit size or the number of values of a range proof is not supported.
+    InvalidRangeParams(String),
+    /// A proof attached to a response does not verify.
+    InvalidProof,
+    /// The threshold, the number of shares or the share indices are unusable.
+    InvalidSharingParams(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidLength { expected, actual } => {
+                write!(
+                    f,
+                    "invalid length: expected {} bytes, got {}",
+                    expected, actual
+                )
+            }
+            Error::NonCanonicalEncoding => write!(f, "non-canonical encoding"),
+            Error::IdentityPoint => write!(f, "unexpected identity point"),
+            Error::OffCurvePoint => write!(f, "bytes do not encode a point of the group"),
+            Error::UnknownCurve(name) => write!(f, "unknown elliptic curve: {}", name),
+            Error::InvalidKdfParams(reason) => write!(f, "invalid KDF parameters: {}", reason),
+            Error::InvalidWitness => write!(f, "witness does not satisfy the statement"),
+            Error::InvalidGroupParams(reason) => write!(f, "invalid group parameters: {}", reason),
+            Error::InvalidRangeParams(reason) => {
+                write!(f, "invalid range proof parameters: {}", reason)
+            }
+            Error::InvalidProof => write!(f, "proof verification failed"),
+            Error::InvalidSharingParams(reason) => {
+                write!(f, "invalid secret sharing parameters: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
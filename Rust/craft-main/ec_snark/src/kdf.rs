@@ -0,0 +1,239 @@
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::secret::SecretScalar;
+use crate::Error;
+use argon2::{Algorithm, Argon2, Params, Version};
+use pasta_curves::group::ff::{Field, PrimeField};
+use rand_core::{OsRng, RngCore};
+use zeroize::Zeroize;
+
+/// Length in bytes of the per-user salt.
+pub const SALT_LEN: usize = 16;
+
+/// Child indices from `HARDENED` on are derived from the master secret only.
+pub const HARDENED: u32 = 1 << 31;
+
+/// Domain-separation prefix of the hash of [`derive_child`].
+const CHILD_DOMAIN: &str = "ec_snark.kdf.child.v1";
+
+/// Cost parameters of Argon2id.
+///
+/// The defaults follow the OWASP recommendation of 19 MiB of memory, two passes
+/// and one lane. Every device deriving the same secret must use the same values.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory size in KiB.
+    pub memory_kib: u32,
+    /// Number of passes over the memory.
+    pub iterations: u32,
+    /// Degree of parallelism.
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+/// Generates a fresh random salt.
+pub fn random_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+/// Derives a secret scalar from a password with Argon2id.
+///
+/// The 64-byte Argon2id output is reduced to a scalar with
+/// [`CurveBackend::scalar_from_uniform_bytes`], so the same password and salt
+/// give the same secret on every device.
+///
+/// # Arguments
+/// * `password` - The user's password.
+/// * `salt` - The user's salt of [`SALT_LEN`] bytes.
+/// * `params` - Argon2id cost parameters.
+///
+/// # Returns
+/// The derived scalar, [`Error::InvalidLength`] for a salt of the wrong size, or
+/// [`Error::InvalidKdfParams`] if Argon2id rejects the cost parameters.
+pub fn derive_secret<G: CurveBackend>(
+    password: &[u8],
+    salt: &[u8],
+    params: &KdfParams,
+) -> Result<SecretScalar<G::Scalar>, Error> {
+    if salt.len() != SALT_LEN {
+        return Err(Error::InvalidLength {
+            expected: SALT_LEN,
+            actual: salt.len(),
+        });
+    }
+    let argon2_params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(64),
+    )
+    .map_err(|e| Error::InvalidKdfParams(e.to_string()))?;
+    let mut output = [0u8; 64];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, argon2_params)
+        .hash_password_into(password, salt, &mut output)
+        .map_err(|e| Error::InvalidKdfParams(e.to_string()))?;
+    let secret = SecretScalar::new(G::scalar_from_uniform_bytes(&output));
+    output.zeroize();
+    Ok(secret)
+}
+
+/// Hashes the service identifier and the index, after a tag and the parent key.
+fn child_hash<G: CurveBackend>(tag: u8, parent: &[u8], service_id: &str, index: u32) -> G::Scalar {
+    let mut message = vec![tag];
+    message.extend_from_slice(parent);
+    message.extend_from_slice(&(service_id.len() as u64).to_le_bytes());
+    message.extend_from_slice(service_id.as_bytes());
+    message.extend_from_slice(&index.to_be_bytes());
+    let scalar = G::hash_to_scalar(CHILD_DOMAIN, &message);
+    message.zeroize();
+    scalar
+}
+
+/// Derives the secret of one relying party from a master secret.
+///
+/// Children of different services or indices are independent scalars, so their
+/// registrations cannot be linked without the master secret. Like in BIP32, an
+/// index below [`HARDENED`] gives `x + H(g^x, service_id, index)`, whose public key
+/// [`derive_child_public`] can compute from the master public key alone; an index
+/// from [`HARDENED`] on gives `H(x, service_id, index)`, for which it cannot. A
+/// non-hardened child and the master public key reveal the master secret, so
+/// children handed to servers should be hardened.
+///
+/// # Arguments
+/// * `master` - The master secret, e.g. from [`derive_secret`].
+/// * `service_id` - The identity of the relying party, e.g. its host name.
+/// * `index` - The child index, distinguishing several accounts at one service.
+///
+/// # Returns
+/// The child secret, or [`Error::IdentityPoint`] if it is zero.
+pub fn derive_child<G: CurveBackend>(
+    master: &SecretScalar<G::Scalar>,
+    service_id: &str,
+    index: u32,
+) -> Result<SecretScalar<G::Scalar>, Error> {
+    let x = master.expose_secret();
+    let child = if index >= HARDENED {
+        let mut repr = x.to_repr();
+        let child = child_hash::<G>(0, repr.as_ref(), service_id, index);
+        repr.as_mut().zeroize();
+        child
+    } else {
+        let public = G::to(&(G::generator() * x));
+        *x + child_hash::<G>(1, &public, service_id, index)
+    };
+    if bool::from(child.is_zero()) {
+        return Err(Error::IdentityPoint);
+    }
+    Ok(SecretScalar::new(child))
+}
+
+/// Derives the public key `G^x_child` of a non-hardened child from `G^x`, where
+/// `G` is the standard generator of the group.
+///
+/// # Returns
+/// The child public key, or [`Error::InvalidKdfParams`] for a hardened index.
+pub fn derive_child_public<G: CurveBackend>(
+    master_public: &G,
+    service_id: &str,
+    index: u32,
+) -> Result<G, Error> {
+    if index >= HARDENED {
+        return Err(Error::InvalidKdfParams(format!(
+            "hardened index {} needs the master secret",
+            index
+        )));
+    }
+    let tweak = child_hash::<G>(1, &G::to(master_public), service_id, index);
+    Ok(*master_public + G::generator() * tweak)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pasta_curves::group::Group;
+    use pasta_curves::pallas::Point;
+
+    /// Cheap parameters to keep the tests fast.
+    const TEST_PARAMS: KdfParams = KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn derive_secret_is_deterministic_and_salted() {
+        let salt = random_salt();
+        let a = derive_secret::<Point>(b"password", &salt, &TEST_PARAMS).unwrap();
+        let b = derive_secret::<Point>(b"password", &salt, &TEST_PARAMS).unwrap();
+        assert_eq!(a, b);
+
+        let other_salt = derive_secret::<Point>(b"password", &random_salt(), &TEST_PARAMS);
+        assert_ne!(other_salt.unwrap(), a);
+        let other_password = derive_secret::<Point>(b"passw0rd", &salt, &TEST_PARAMS);
+        assert_ne!(other_password.unwrap(), a);
+    }
+
+    #[test]
+    fn derive_secret_rejects_bad_input() {
+        assert_eq!(
+            derive_secret::<Point>(b"password", &[0u8; 8], &TEST_PARAMS),
+            Err(Error::InvalidLength {
+                expected: SALT_LEN,
+                actual: 8,
+            })
+        );
+        let params = KdfParams {
+            iterations: 0,
+            ..TEST_PARAMS
+        };
+        assert!(matches!(
+            derive_secret::<Point>(b"password", &random_salt(), &params),
+            Err(Error::InvalidKdfParams(_))
+        ));
+    }
+
+    #[test]
+    fn derive_child_separates_services() {
+        let master = derive_secret::<Point>(b"password", &random_salt(), &TEST_PARAMS).unwrap();
+        let a = derive_child::<Point>(&master, "a.example:50051", HARDENED).unwrap();
+        assert_eq!(
+            derive_child::<Point>(&master, "a.example:50051", HARDENED).unwrap(),
+            a
+        );
+        let children = [
+            derive_child::<Point>(&master, "b.example:50051", HARDENED).unwrap(),
+            derive_child::<Point>(&master, "a.example:50051", HARDENED + 1).unwrap(),
+            derive_child::<Point>(&master, "a.example:50051", 0).unwrap(),
+            derive_child::<Point>(&master, "a.example:5005", HARDENED).unwrap(),
+        ];
+        for child in &children {
+            assert_ne!(*child, a);
+            assert_ne!(*child, master);
+        }
+    }
+
+    #[test]
+    fn derive_child_public_matches_non_hardened_children() {
+        let master = SecretScalar::<<Point as Group>::Scalar>::random(&mut OsRng);
+        let master_public = Point::generator() * master.expose_secret();
+        let child = derive_child::<Point>(&master, "a.example", 7).unwrap();
+        assert_eq!(
+            derive_child_public(&master_public, "a.example", 7),
+            Ok(Point::generator() * child.expose_secret())
+        );
+        assert!(matches!(
+            derive_child_public(&master_public, "a.example", HARDENED),
+            Err(Error::InvalidKdfParams(_))
+        ));
+    }
+}
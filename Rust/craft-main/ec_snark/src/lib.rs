@@ -2,3 +2,14 @@ pub mod protocol;
 
 /// Common types for server and client.
 pub mod common;
+
+/// Password-based derivation of secrets.
+pub mod kdf;
+
+/// Zeroizing wrappers of secret scalars.
+pub mod secret;
+
+/// Error type shared by the decoding and protocol APIs.
+pub mod error;
+
+pub use error::Error;
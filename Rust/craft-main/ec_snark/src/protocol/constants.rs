@@ -1,100 +1,114 @@
-use crate::common::FromBytes;
+use crate::common::{EllipticCurve, FromBytes};
+use crate::protocol::elliptic_curves::CurveBackend;
 use crate::protocol::GroupParams;
+use crate::Error;
+use bls12_381::G1Projective as Bls12381Point;
+use curve25519_dalek::ristretto::RistrettoPoint;
 use lazy_static::lazy_static;
 #[allow(unused_imports)]
 use num_traits::FromBytes as NumFromBytes;
+use pasta_curves::group::ff::PrimeField;
 use pasta_curves::pallas::Point as PallasPoint;
 use pasta_curves::vesta::Point as VestaPoint;
 use std::str::FromStr;
 
+/// The public seed every shipped set of group parameters is derived from.
+pub const GROUP_PARAMS_SEED: &str = "ec_snark-group-params-v1";
+
 lazy_static! {
+    /// `GroupParams::derive(&EllipticCurve::Pallas, GROUP_PARAMS_SEED)`.
     pub static ref PALLAS_GROUP_PARAMS: GroupParams<PallasPoint> = {
-        GroupParams::<PallasPoint> {
-            g: <PallasPoint as FromBytes<PallasPoint>>::from(
+        GroupParams::<PallasPoint>::new(
+            <PallasPoint as FromBytes<PallasPoint>>::from(
                 convert(
                     &hex::decode(
-                        "f9abd1b1a37af310baa363ed031ef5613fb474f1780dc8fc767c2b1480da582b",
+                        "7312777b21e0bbc20c5957f052cb43d4db663379a3fe654a4132e0ee8f2d6f3b",
                     )
                     .unwrap(),
                 )
                 .unwrap(),
             )
             .unwrap(),
-            h: <PallasPoint as FromBytes<PallasPoint>>::from(
+            <PallasPoint as FromBytes<PallasPoint>>::from(
                 convert(
                     &hex::decode(
-                        "8f1339a6e025db7854f67838a42764b870e85e991e7b2e6570c5e5fee6e5c30c",
+                        "754e483b03af3bbf773fc2cd8d89c767828ba234034fe5f7f96e932f070fe7be",
                     )
                     .unwrap(),
                 )
                 .unwrap(),
             )
             .unwrap(),
-            p: <PallasPoint as FromBytes<PallasPoint>>::from(
+        )
+    };
+    /// `GroupParams::derive(&EllipticCurve::Vesta, GROUP_PARAMS_SEED)`.
+    pub static ref VESTA_GROUP_PARAMS: GroupParams<VestaPoint> = {
+        GroupParams::<VestaPoint>::new(
+            <VestaPoint as FromBytes<VestaPoint>>::from(
                 convert(
                     &hex::decode(
-                        "0000000000000000000000000000000000000000000000000000000000000000",
+                        "39d6a346e9f23da7798ac806d3c841a57ec990f48cbba4fcbb1fc26885325716",
                     )
                     .unwrap(),
                 )
                 .unwrap(),
             )
             .unwrap(),
-            q: <PallasPoint as FromBytes<PallasPoint>>::from(
+            <VestaPoint as FromBytes<VestaPoint>>::from(
                 convert(
                     &hex::decode(
-                        "0000000000000000000000000000000000000000000000000000000000000000",
+                        "2b946d279f9ba5d5cffd53a7c10841833beeb3df404272b96bd29806432a5484",
                     )
                     .unwrap(),
                 )
                 .unwrap(),
             )
             .unwrap(),
-        }
+        )
     };
-    pub static ref VESTA_GROUP_PARAMS: GroupParams<VestaPoint> = {
-        GroupParams::<VestaPoint> {
-            g: <VestaPoint as FromBytes<VestaPoint>>::from(
+    /// `GroupParams::derive(&EllipticCurve::Ristretto, GROUP_PARAMS_SEED)`.
+    pub static ref RISTRETTO_GROUP_PARAMS: GroupParams<RistrettoPoint> = {
+        GroupParams::<RistrettoPoint>::new(
+            <RistrettoPoint as FromBytes<RistrettoPoint>>::from(
                 convert(
                     &hex::decode(
-                        "227b13b3f09fbc6312ea3a7d150e9879fc5debc5f19e0433a0d774e7485e7ea3",
+                        "66c9677b6021d06c4ba65a964912917a9bfe69b46ef4c072a6b787bb1d40706e",
                     )
                     .unwrap(),
                 )
                 .unwrap(),
             )
             .unwrap(),
-            h: <VestaPoint as FromBytes<VestaPoint>>::from(
+            <RistrettoPoint as FromBytes<RistrettoPoint>>::from(
                 convert(
                     &hex::decode(
-                        "33fc580619f0b5fa23a88cb6be070033cfdb0ed10aef7491d2400ea6dd45f5a6",
+                        "389d96ee879c77b2af978fb4b561ba79c1c82ee58814f17be17457b895dbbd14",
                     )
                     .unwrap(),
                 )
                 .unwrap(),
             )
             .unwrap(),
-            p: <VestaPoint as FromBytes<VestaPoint>>::from(
-                convert(
-                    &hex::decode(
-                        "0000000000000000000000000000000000000000000000000000000000000000",
-                    )
-                    .unwrap(),
+        )
+    };
+    /// `GroupParams::derive(&EllipticCurve::Bls12381, GROUP_PARAMS_SEED)`.
+    pub static ref BLS12_381_GROUP_PARAMS: GroupParams<Bls12381Point> = {
+        GroupParams::<Bls12381Point>::new(
+            <Bls12381Point as FromBytes<Bls12381Point>>::from(
+                &hex::decode(
+                    "aecc2715f02143a91abfe019d85a3468124b00e082e83249c75b6c1619a9894b730286d095567113290552456ae42e58",
                 )
                 .unwrap(),
             )
             .unwrap(),
-            q: <VestaPoint as FromBytes<VestaPoint>>::from(
-                convert(
-                    &hex::decode(
-                        "0000000000000000000000000000000000000000000000000000000000000000",
-                    )
-                    .unwrap(),
+            <Bls12381Point as FromBytes<Bls12381Point>>::from(
+                &hex::decode(
+                    "b791f694e8228b171e7c511dc7a4096f6fa36fd471da8d6c883abb35d0f0023b60803f4944e9c0948e051a85e6b763a0",
                 )
                 .unwrap(),
             )
             .unwrap(),
-        }
+        )
     };
 }
 
@@ -110,27 +124,166 @@ fn convert(vec: &Vec<u8>) -> Result<&[u8; 32], &'static str> {
     }
 }
 
-impl FromStr for GroupParams<PallasPoint> {
-    type Err = (); // Defining the error type as a unit type.
+impl<G: CurveBackend> GroupParams<G> {
+    /// Creates group parameters from two generators and the metadata of `G`.
+    pub fn new(g: G, h: G) -> Self {
+        Self {
+            curve: G::CURVE,
+            g,
+            h,
+            field_modulus: G::FIELD_MODULUS,
+            order: <G::Scalar as PrimeField>::MODULUS,
+            cofactor: G::COFACTOR,
+        }
+    }
 
-    // Implementing the from_str method which takes a string slice and returns a Result.
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "pallas" => Ok(PALLAS_GROUP_PARAMS.to_owned()),
-            _ => Err(()), // Returning an error for unrecognized strings.
+    /// Derives group parameters from a public seed string.
+    ///
+    /// Both generators are hashed to the group with [`CurveBackend::hash_to_group`],
+    /// using the domain `"<seed>:<curve>"` and the messages `"g"` and `"h"`. Since
+    /// neither is the multiple of a known point, nobody knows `log_g(h)`, and anybody
+    /// can check this by recomputing them.
+    ///
+    /// # Returns
+    /// The derived parameters, or [`Error::UnknownCurve`] if `curve` does not match `G`.
+    pub fn derive(curve: &EllipticCurve, seed: &str) -> Result<Self, Error> {
+        if *curve != G::CURVE {
+            return Err(Error::UnknownCurve(curve.to_string()));
         }
+        let domain = format!("{}:{}", seed, curve);
+        Ok(Self::new(
+            G::hash_to_group(&domain, b"g"),
+            G::hash_to_group(&domain, b"h"),
+        ))
+    }
+
+    /// Checks that the parameters can be used with the protocol.
+    ///
+    /// The curve metadata is taken from `G` by construction, so only the generators
+    /// are checked: both must be non-identity elements of the prime-order group and
+    /// differ from each other.
+    ///
+    /// # Returns
+    /// [`Error::IdentityPoint`] if a generator is the identity, and
+    /// [`Error::InvalidGroupParams`] if the generators are equal or a generator has
+    /// a torsion component.
+    pub fn validate(&self) -> Result<(), Error> {
+        if bool::from(self.g.is_identity()) || bool::from(self.h.is_identity()) {
+            return Err(Error::IdentityPoint);
+        }
+        if self.g == self.h {
+            return Err(Error::InvalidGroupParams("g and h are equal".to_string()));
+        }
+        if !self.g.is_torsion_free() || !self.h.is_torsion_free() {
+            return Err(Error::InvalidGroupParams(
+                "generator outside the prime-order group".to_string(),
+            ));
+        }
+        Ok(())
     }
 }
 
-impl FromStr for GroupParams<VestaPoint> {
-    type Err = (); // Defining the error type as a unit type.
+impl<G: CurveBackend> FromStr for GroupParams<G> {
+    type Err = Error;
 
-    // Implementing the from_str method which takes a string slice and returns a Result.
+    // Parses the curve name and returns the group parameters of `G` if they match.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            // Matching the string "ec25519" and returning the corresponding group parameters.
-            "vesta" => Ok(VESTA_GROUP_PARAMS.to_owned()),
-            _ => Err(()), // Returning an error for unrecognized strings.
+        match EllipticCurve::from_str(s) {
+            Ok(curve) if curve == G::CURVE => Ok(G::group_params()),
+            _ => Err(Error::UnknownCurve(s.to_string())),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pasta_curves::group::Group;
+
+    fn assert_derived<G: CurveBackend>(curve: EllipticCurve, shipped: &GroupParams<G>) {
+        let derived = GroupParams::<G>::derive(&curve, GROUP_PARAMS_SEED).unwrap();
+        assert_eq!(derived.g, shipped.g);
+        assert_eq!(derived.h, shipped.h);
+        assert_ne!(derived.g, derived.h);
+    }
+
+    #[test]
+    fn shipped_params_are_derived_from_seed() {
+        assert_derived(EllipticCurve::Pallas, &PALLAS_GROUP_PARAMS);
+        assert_derived(EllipticCurve::Vesta, &VESTA_GROUP_PARAMS);
+        assert_derived(EllipticCurve::Ristretto, &RISTRETTO_GROUP_PARAMS);
+        assert_derived(EllipticCurve::Bls12381, &BLS12_381_GROUP_PARAMS);
+    }
+
+    #[test]
+    fn derive_depends_on_seed_and_curve() {
+        let a = GroupParams::<PallasPoint>::derive(&EllipticCurve::Pallas, "a").unwrap();
+        let b = GroupParams::<PallasPoint>::derive(&EllipticCurve::Pallas, "b").unwrap();
+        assert_ne!(a.g, b.g);
+        assert_ne!(a.h, b.h);
+        assert_eq!(
+            GroupParams::<PallasPoint>::derive(&EllipticCurve::Vesta, "a").unwrap_err(),
+            Error::UnknownCurve("vesta".to_string())
+        );
+    }
+
+    #[test]
+    fn shipped_params_are_valid() {
+        assert_eq!(PALLAS_GROUP_PARAMS.validate(), Ok(()));
+        assert_eq!(VESTA_GROUP_PARAMS.validate(), Ok(()));
+        assert_eq!(RISTRETTO_GROUP_PARAMS.validate(), Ok(()));
+        assert_eq!(BLS12_381_GROUP_PARAMS.validate(), Ok(()));
+        assert_eq!(PALLAS_GROUP_PARAMS.field_modulus, VESTA_GROUP_PARAMS.order);
+        assert_eq!(VESTA_GROUP_PARAMS.field_modulus, PALLAS_GROUP_PARAMS.order);
+        // Every element of these groups is torsion-free, so none has a cofactor.
+        assert_eq!(PALLAS_GROUP_PARAMS.cofactor, "0x1");
+        assert_eq!(VESTA_GROUP_PARAMS.cofactor, "0x1");
+        assert_eq!(RISTRETTO_GROUP_PARAMS.cofactor, "0x1");
+    }
+
+    #[test]
+    fn validate_rejects_bad_params() {
+        let params = *PALLAS_GROUP_PARAMS;
+        let identity = GroupParams::new(params.g, PallasPoint::identity());
+        assert_eq!(identity.validate(), Err(Error::IdentityPoint));
+        let equal = GroupParams::new(params.g, params.g);
+        assert!(matches!(
+            equal.validate(),
+            Err(Error::InvalidGroupParams(_))
+        ));
+    }
+
+    #[test]
+    fn validate_rejects_torsion_points() {
+        // (0, 2) has order 3 on the BLS12-381 G1 curve, and 3 divides the cofactor only.
+        let mut bytes = [0u8; 48];
+        bytes[0] = 0x80;
+        let torsion = bls12_381::G1Affine::from_compressed_unchecked(&bytes).unwrap();
+        let torsion: Bls12381Point = torsion.into();
+        assert!(bool::from((torsion + torsion + torsion).is_identity()));
+        let params = GroupParams::new(BLS12_381_GROUP_PARAMS.g, torsion);
+        assert!(matches!(
+            params.validate(),
+            Err(Error::InvalidGroupParams(_))
+        ));
+        // A generator with both a prime-order and a torsion component is rejected too.
+        let mixed = GroupParams::new(BLS12_381_GROUP_PARAMS.g + torsion, BLS12_381_GROUP_PARAMS.h);
+        assert!(matches!(
+            mixed.validate(),
+            Err(Error::InvalidGroupParams(_))
+        ));
+    }
+
+    #[test]
+    fn from_str_rejects_unknown_curve() {
+        assert!(GroupParams::<PallasPoint>::from_str("pallas").is_ok());
+        assert_eq!(
+            GroupParams::<PallasPoint>::from_str("secp256k1").unwrap_err(),
+            Error::UnknownCurve("secp256k1".to_string())
+        );
+        assert_eq!(
+            GroupParams::<PallasPoint>::from_str("vesta").unwrap_err(),
+            Error::UnknownCurve("vesta".to_string())
+        );
+    }
+}
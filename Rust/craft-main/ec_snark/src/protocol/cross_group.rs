@@ -0,0 +1,551 @@
+use crate::common::{self, FromBytes, IntoBytes};
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::msm::multiscalar_mul;
+use crate::protocol::transcript::Transcript;
+use crate::protocol::GroupParams;
+use crate::secret::{Nonce, SecretScalar};
+use crate::Error;
+use pasta_curves::group::ff::{Field, PrimeField, PrimeFieldBits};
+use rand_core::{CryptoRng, RngCore};
+
+/// Domain-separation label of cross-group proof transcripts.
+pub const CROSS_GROUP_DOMAIN: &[u8] = b"ec_snark.crossgroup.v1";
+
+/// The length of the challenges in bytes.
+///
+/// Challenges are 128-bit integers, which are smaller than the order of every
+/// supported group and hence mean the same in both scalar fields.
+pub const CHALLENGE_LEN: usize = 16;
+
+/// The proof for one bit `b_i` of the secret.
+///
+/// `C_i = b_i * g + r_i * h` and `D_i = b_i * g' + s_i * h'` commit to the bit on
+/// both curves, and a ring proof shows that both open to 0 or both open to 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitProof<A: CurveBackend, B: CurveBackend> {
+    /// The commitment `C_i` on the first curve.
+    pub commitment_a: A,
+    /// The commitment `D_i` on the second curve.
+    pub commitment_b: B,
+    /// The challenge of the branch `b_i = 0`; that of `b_i = 1` is `c XOR c_0`.
+    pub c0: u128,
+    /// The responses of both branches on the first curve.
+    pub z_a: [A::Scalar; 2],
+    /// The responses of both branches on the second curve.
+    pub z_b: [B::Scalar; 2],
+}
+
+/// A proof that `y_a = g^x` on one curve and `y_b = g'^x` on another share `x`.
+///
+/// The scalar fields of the curves differ, so `x` is proven to be an integer in
+/// `[0, 2^n)`, which is the same in both fields, by committing to each of its bits
+/// on both curves. The blindings are chosen such that the weighted sums of the bit
+/// commitments are `y_a` and `y_b` themselves. The proof grows linearly with `n`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrossGroupProof<A: CurveBackend, B: CurveBackend> {
+    /// The common Fiat–Shamir challenge `c`.
+    pub challenge: u128,
+    /// One proof per bit, least significant first.
+    pub bits: Vec<BitProof<A, B>>,
+}
+
+/// The largest supported bit size `n`, such that `2^n` fits into both scalar fields.
+pub fn max_bits<A: CurveBackend, B: CurveBackend>() -> usize {
+    A::Scalar::CAPACITY.min(B::Scalar::CAPACITY) as usize
+}
+
+/// Checks that `n` bits are supported by both curves.
+fn check_bits<A: CurveBackend, B: CurveBackend>(n: usize) -> Result<(), Error> {
+    if n == 0 || n > max_bits::<A, B>() {
+        return Err(Error::InvalidRangeParams(format!(
+            "unsupported bit size {} for {} and {}",
+            n,
+            A::CURVE,
+            B::CURVE
+        )));
+    }
+    Ok(())
+}
+
+/// Maps the integer value of `x` into another scalar field.
+///
+/// # Returns
+/// The same integer in the field of `F2`, or [`Error::InvalidWitness`] if `x` is not
+/// smaller than `2^n`, or [`Error::InvalidRangeParams`] if `n` does not fit into
+/// one of the fields.
+pub fn embed_scalar<F1: PrimeFieldBits, F2: PrimeField>(
+    x: &SecretScalar<F1>,
+    n: usize,
+) -> Result<SecretScalar<F2>, Error> {
+    if n == 0 || n > F1::CAPACITY.min(F2::CAPACITY) as usize {
+        return Err(Error::InvalidRangeParams(format!(
+            "unsupported bit size {}",
+            n
+        )));
+    }
+    let bits = x.expose_secret().to_le_bits();
+    if bits.iter().skip(n).any(|bit| *bit) {
+        return Err(Error::InvalidWitness);
+    }
+    let value = bits
+        .iter()
+        .take(n)
+        .rev()
+        .fold(F2::ZERO, |acc, bit| acc.double() + F2::from(*bit as u64));
+    Ok(SecretScalar::new(value))
+}
+
+/// Draws a uniformly random 128-bit challenge.
+fn random_challenge<R: RngCore + CryptoRng>(rng: &mut R) -> u128 {
+    let mut bytes = [0u8; CHALLENGE_LEN];
+    rng.fill_bytes(&mut bytes);
+    u128::from_le_bytes(bytes)
+}
+
+/// Starts the transcript of a proof, bound to the context and the public keys.
+fn start_transcript<A: CurveBackend, B: CurveBackend>(
+    context: &[u8],
+    n: usize,
+    y_a: &A,
+    y_b: &B,
+) -> Transcript {
+    let mut transcript = Transcript::new(CROSS_GROUP_DOMAIN);
+    transcript.append_message(b"context", context);
+    transcript.append_message(b"curve-a", A::CURVE.to_string().as_bytes());
+    transcript.append_message(b"curve-b", B::CURVE.to_string().as_bytes());
+    transcript.append_message(b"n", &(n as u64).to_le_bytes());
+    transcript.append_element(b"y-a", y_a);
+    transcript.append_element(b"y-b", y_b);
+    transcript
+}
+
+/// Derives the common challenge from the transcript.
+fn transcript_challenge(transcript: &mut Transcript) -> u128 {
+    let bytes = transcript.challenge_bytes(b"c");
+    u128::from_le_bytes(bytes[..CHALLENGE_LEN].try_into().unwrap())
+}
+
+/// Computes `[1, 2, 4, ..., 2^(n-1)]`.
+fn powers_of_two<F: Field>(n: usize) -> Vec<F> {
+    std::iter::successors(Some(F::ONE), |p| Some(p.double()))
+        .take(n)
+        .collect()
+}
+
+/// Picks blindings `r_i` with `sum 2^i r_i = 0`, by solving for the last one.
+fn balanced_blindings<F: Field, R: RngCore + CryptoRng>(n: usize, rng: &mut R) -> Vec<Nonce<F>> {
+    let powers = powers_of_two::<F>(n);
+    let mut blindings: Vec<_> = (1..n).map(|_| Nonce::<F>::random(rng)).collect();
+    let sum = blindings
+        .iter()
+        .zip(&powers)
+        .fold(F::ZERO, |acc, (r, p)| acc + *r.expose_secret() * p);
+    // 2^(n-1) is not zero in a field of odd characteristic.
+    blindings.push(Nonce::new(-sum * powers[n - 1].invert().unwrap()));
+    blindings
+}
+
+impl<A: CurveBackend, B: CurveBackend> CrossGroupProof<A, B> {
+    /// Proves that the public keys `g^x` on both curves share the secret `x`.
+    ///
+    /// The prover branches on the bits of `x`, so it should run on the user's own
+    /// device rather than next to an observer of its timing.
+    ///
+    /// # Arguments
+    /// * `params_a` - Group parameters of the first curve; `x` is taken in its field.
+    /// * `params_b` - Group parameters of the second curve.
+    /// * `context` - Application data the proof is bound to, e.g. an account identifier.
+    /// * `x` - The secret, smaller than `2^n`.
+    /// * `n` - The bit size of the secret, at most [`max_bits`].
+    /// * `rng` - The source of the prover's randomness.
+    ///
+    /// # Returns
+    /// The proof and the public keys `(g^x, g'^x)` it is about, [`Error::InvalidRangeParams`]
+    /// for unsupported sizes, [`Error::InvalidWitness`] if `x` is out of range, or
+    /// [`Error::IdentityPoint`] if `x` is zero.
+    pub fn prove<R: RngCore + CryptoRng>(
+        params_a: &GroupParams<A>,
+        params_b: &GroupParams<B>,
+        context: &[u8],
+        x: &SecretScalar<A::Scalar>,
+        n: usize,
+        rng: &mut R,
+    ) -> Result<(Self, A, B), Error> {
+        check_bits::<A, B>(n)?;
+        let x_b = embed_scalar::<A::Scalar, B::Scalar>(x, n)?;
+        if bool::from(x.expose_secret().is_zero()) {
+            return Err(Error::IdentityPoint);
+        }
+        let y_a = params_a.g * x.expose_secret();
+        let y_b = params_b.g * x_b.expose_secret();
+        let bits: Vec<bool> = x
+            .expose_secret()
+            .to_le_bits()
+            .iter()
+            .take(n)
+            .map(|b| *b)
+            .collect();
+
+        let r = balanced_blindings::<A::Scalar, R>(n, rng);
+        let s = balanced_blindings::<B::Scalar, R>(n, rng);
+        let mut transcript = start_transcript(context, n, &y_a, &y_b);
+        let mut states = Vec::with_capacity(n);
+        let mut proofs = Vec::with_capacity(n);
+        for (i, bit) in bits.iter().enumerate() {
+            let (b, other) = (*bit as usize, 1 - *bit as usize);
+            let commitment_a =
+                params_a.g * A::Scalar::from(b as u64) + params_a.h * r[i].expose_secret();
+            let commitment_b =
+                params_b.g * B::Scalar::from(b as u64) + params_b.h * s[i].expose_secret();
+
+            // The branch of the actual bit gets fresh nonces, the other one is simulated.
+            let k_a = Nonce::<A::Scalar>::random(rng);
+            let k_b = Nonce::<B::Scalar>::random(rng);
+            let c_other = random_challenge(rng);
+            let mut z_a = [A::Scalar::ZERO; 2];
+            let mut z_b = [B::Scalar::ZERO; 2];
+            z_a[other] = A::Scalar::random(&mut *rng);
+            z_b[other] = B::Scalar::random(&mut *rng);
+            let mut nonces_a = [A::identity(); 2];
+            let mut nonces_b = [B::identity(); 2];
+            nonces_a[b] = params_a.h * k_a.expose_secret();
+            nonces_b[b] = params_b.h * k_b.expose_secret();
+            nonces_a[other] = simulated_nonce(params_a, &commitment_a, other, c_other, &z_a[other]);
+            nonces_b[other] = simulated_nonce(params_b, &commitment_b, other, c_other, &z_b[other]);
+
+            transcript.append_element(b"C", &commitment_a);
+            transcript.append_element(b"D", &commitment_b);
+            for j in 0..2 {
+                transcript.append_element(b"R", &nonces_a[j]);
+                transcript.append_element(b"S", &nonces_b[j]);
+            }
+            states.push((b, c_other, k_a, k_b));
+            proofs.push(BitProof {
+                commitment_a,
+                commitment_b,
+                c0: 0,
+                z_a,
+                z_b,
+            });
+        }
+
+        let challenge = transcript_challenge(&mut transcript);
+        for (i, ((b, c_other, k_a, k_b), proof)) in states.into_iter().zip(&mut proofs).enumerate()
+        {
+            let c_bit = challenge ^ c_other;
+            proof.c0 = if b == 0 { c_bit } else { c_other };
+            proof.z_a[b] = k_a.consume() + A::Scalar::from_u128(c_bit) * r[i].expose_secret();
+            proof.z_b[b] = k_b.consume() + B::Scalar::from_u128(c_bit) * s[i].expose_secret();
+        }
+        Ok((
+            Self {
+                challenge,
+                bits: proofs,
+            },
+            y_a,
+            y_b,
+        ))
+    }
+
+    /// The bit size `n` of the proven range.
+    pub fn bit_size(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Verifies that the public keys share their secret.
+    ///
+    /// # Arguments
+    /// * `params_a` - Group parameters of the first curve.
+    /// * `params_b` - Group parameters of the second curve.
+    /// * `context` - The application data the proof was bound to.
+    /// * `y_a` - The public key `g^x` on the first curve.
+    /// * `y_b` - The public key `g'^x` on the second curve.
+    ///
+    /// # Returns
+    /// Whether the proof is valid, or [`Error::IdentityPoint`] if a public key is
+    /// the identity.
+    pub fn verify(
+        &self,
+        params_a: &GroupParams<A>,
+        params_b: &GroupParams<B>,
+        context: &[u8],
+        y_a: &A,
+        y_b: &B,
+    ) -> Result<bool, Error> {
+        if bool::from(y_a.is_identity()) || bool::from(y_b.is_identity()) {
+            return Err(Error::IdentityPoint);
+        }
+        let n = self.bits.len();
+        if check_bits::<A, B>(n).is_err() {
+            return Ok(false);
+        }
+
+        let commitments_a: Vec<_> = self.bits.iter().map(|bit| bit.commitment_a).collect();
+        let commitments_b: Vec<_> = self.bits.iter().map(|bit| bit.commitment_b).collect();
+        if multiscalar_mul(&powers_of_two(n), &commitments_a) != *y_a
+            || multiscalar_mul(&powers_of_two(n), &commitments_b) != *y_b
+        {
+            return Ok(false);
+        }
+
+        let mut transcript = start_transcript(context, n, y_a, y_b);
+        for bit in &self.bits {
+            let challenges = [bit.c0, self.challenge ^ bit.c0];
+            transcript.append_element(b"C", &bit.commitment_a);
+            transcript.append_element(b"D", &bit.commitment_b);
+            for (j, c) in challenges.into_iter().enumerate() {
+                let nonce_a = simulated_nonce(params_a, &bit.commitment_a, j, c, &bit.z_a[j]);
+                let nonce_b = simulated_nonce(params_b, &bit.commitment_b, j, c, &bit.z_b[j]);
+                transcript.append_element(b"R", &nonce_a);
+                transcript.append_element(b"S", &nonce_b);
+            }
+        }
+        Ok(transcript_challenge(&mut transcript) == self.challenge)
+    }
+}
+
+/// Recomputes the nonce `R = z * h - c * (C - j * g)` of branch `j` of a bit proof.
+fn simulated_nonce<G: CurveBackend>(
+    params: &GroupParams<G>,
+    commitment: &G,
+    j: usize,
+    c: u128,
+    z: &G::Scalar,
+) -> G {
+    let statement = if j == 0 {
+        *commitment
+    } else {
+        *commitment - params.g
+    };
+    params.h * z - statement * G::Scalar::from_u128(c)
+}
+
+impl<A, B> IntoBytes<CrossGroupProof<A, B>> for CrossGroupProof<A, B>
+where
+    A: CurveBackend,
+    B: CurveBackend,
+    A::Scalar: IntoBytes<A::Scalar>,
+    B::Scalar: IntoBytes<B::Scalar>,
+{
+    /// Encodes the bit size, the challenge, and the seven fields of every bit proof.
+    ///
+    /// # Panics
+    /// If the bit size is not supported by both curves, which
+    /// [`CrossGroupProof::prove`] never produces.
+    fn to(t: &CrossGroupProof<A, B>) -> Vec<u8> {
+        let n = check_bits::<A, B>(t.bits.len())
+            .ok()
+            .and_then(|_| u8::try_from(t.bits.len()).ok())
+            .expect("Unsupported bit size of cross-group proof");
+        let mut parts = vec![vec![n], t.challenge.to_le_bytes().to_vec()];
+        for bit in &t.bits {
+            parts.push(A::to(&bit.commitment_a));
+            parts.push(B::to(&bit.commitment_b));
+            parts.push(bit.c0.to_le_bytes().to_vec());
+            parts.extend(bit.z_a.iter().map(A::Scalar::to));
+            parts.extend(bit.z_b.iter().map(B::Scalar::to));
+        }
+        common::encode_parts(&parts)
+    }
+}
+
+impl<A, B> FromBytes<CrossGroupProof<A, B>> for CrossGroupProof<A, B>
+where
+    A: CurveBackend + FromBytes<A>,
+    B: CurveBackend + FromBytes<B>,
+    A::Scalar: FromBytes<A::Scalar>,
+    B::Scalar: FromBytes<B::Scalar>,
+{
+    fn from(bytes: &[u8]) -> Result<CrossGroupProof<A, B>, Error> {
+        let n = match common::first_part(bytes)? {
+            [n] => *n as usize,
+            _ => return Err(Error::NonCanonicalEncoding),
+        };
+        if check_bits::<A, B>(n).is_err() {
+            return Err(Error::NonCanonicalEncoding);
+        }
+        let parts = common::decode_parts(bytes, 2 + 7 * n)?;
+        let challenge = |part: &[u8]| -> Result<u128, Error> {
+            let bytes: [u8; CHALLENGE_LEN] = part.try_into().map_err(|_| Error::InvalidLength {
+                expected: CHALLENGE_LEN,
+                actual: part.len(),
+            })?;
+            Ok(u128::from_le_bytes(bytes))
+        };
+        let bits = parts[2..]
+            .chunks(7)
+            .map(|bit| {
+                Ok(BitProof {
+                    commitment_a: <A as FromBytes<A>>::from(bit[0])?,
+                    commitment_b: <B as FromBytes<B>>::from(bit[1])?,
+                    c0: challenge(bit[2])?,
+                    z_a: [
+                        <A::Scalar as FromBytes<A::Scalar>>::from(bit[3])?,
+                        <A::Scalar as FromBytes<A::Scalar>>::from(bit[4])?,
+                    ],
+                    z_b: [
+                        <B::Scalar as FromBytes<B::Scalar>>::from(bit[5])?,
+                        <B::Scalar as FromBytes<B::Scalar>>::from(bit[6])?,
+                    ],
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(CrossGroupProof {
+            challenge: challenge(parts[1])?,
+            bits,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::constants::{PALLAS_GROUP_PARAMS, VESTA_GROUP_PARAMS};
+    use pasta_curves::{pallas, vesta};
+    use rand_core::OsRng;
+
+    type PastaProof = CrossGroupProof<pallas::Point, vesta::Point>;
+
+    /// A random secret of `n` bits.
+    fn bounded_secret(n: usize) -> SecretScalar<pallas::Scalar> {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes[..n / 8]);
+        SecretScalar::new(pallas::Scalar::from_repr(bytes).unwrap())
+    }
+
+    #[test]
+    fn pallas_vesta_same_secret() {
+        let (pallas_params, vesta_params) = (&*PALLAS_GROUP_PARAMS, &*VESTA_GROUP_PARAMS);
+        let x = bounded_secret(248);
+        let (proof, y_a, y_b) =
+            PastaProof::prove(pallas_params, vesta_params, b"alice", &x, 248, &mut OsRng).unwrap();
+        assert_eq!(proof.bit_size(), 248);
+        assert_eq!(y_a, pallas_params.g * x.expose_secret());
+        let x_b = embed_scalar::<_, vesta::Scalar>(&x, 248).unwrap();
+        assert_eq!(y_b, vesta_params.g * x_b.expose_secret());
+        assert_eq!(
+            proof.verify(pallas_params, vesta_params, b"alice", &y_a, &y_b),
+            Ok(true)
+        );
+        assert_eq!(
+            proof.verify(pallas_params, vesta_params, b"bob", &y_a, &y_b),
+            Ok(false)
+        );
+
+        let bytes = PastaProof::to(&proof);
+        assert_eq!(
+            <PastaProof as FromBytes<_>>::from(&bytes),
+            Ok(proof.clone())
+        );
+        assert_eq!(
+            <PastaProof as FromBytes<_>>::from(&bytes[..bytes.len() - 1]).unwrap_err(),
+            Error::InvalidLength {
+                expected: bytes.len(),
+                actual: bytes.len() - 1
+            }
+        );
+        let mut oversized = bytes.clone();
+        oversized[2] = 255;
+        assert_eq!(
+            <PastaProof as FromBytes<_>>::from(&oversized).unwrap_err(),
+            Error::NonCanonicalEncoding
+        );
+        assert_eq!(
+            <PastaProof as FromBytes<_>>::from(&[0]).unwrap_err(),
+            Error::InvalidLength {
+                expected: 2,
+                actual: 1
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Unsupported bit size of cross-group proof")]
+    fn encode_too_many_bits() {
+        let (pallas_params, vesta_params) = (&*PALLAS_GROUP_PARAMS, &*VESTA_GROUP_PARAMS);
+        let x = bounded_secret(64);
+        let (mut proof, _, _) =
+            PastaProof::prove(pallas_params, vesta_params, b"alice", &x, 64, &mut OsRng).unwrap();
+        let bit = proof.bits[0].clone();
+        proof
+            .bits
+            .resize(max_bits::<pallas::Point, vesta::Point>() + 1, bit);
+        PastaProof::to(&proof);
+    }
+
+    #[test]
+    fn pallas_vesta_mismatched_secrets() {
+        let (pallas_params, vesta_params) = (&*PALLAS_GROUP_PARAMS, &*VESTA_GROUP_PARAMS);
+        let x = bounded_secret(64);
+        let (proof, y_a, y_b) =
+            PastaProof::prove(pallas_params, vesta_params, b"alice", &x, 64, &mut OsRng).unwrap();
+
+        // Another user's Vesta registration.
+        let other = embed_scalar::<_, vesta::Scalar>(&bounded_secret(64), 64).unwrap();
+        let other_b = vesta_params.g * other.expose_secret();
+        assert_eq!(
+            proof.verify(pallas_params, vesta_params, b"alice", &y_a, &other_b),
+            Ok(false)
+        );
+
+        // Bit commitments that open to different bits on the two curves, but still
+        // add up to the public key.
+        let mut forged = proof.clone();
+        forged.bits[0].commitment_b += vesta_params.g + vesta_params.g;
+        forged.bits[1].commitment_b -= vesta_params.g;
+        assert_eq!(
+            forged.verify(pallas_params, vesta_params, b"alice", &y_a, &y_b),
+            Ok(false)
+        );
+
+        // The same integer with its lowest bit flipped, proven honestly for Pallas only.
+        let mut repr = x.expose_secret().to_repr();
+        repr[0] ^= 1;
+        let flipped = SecretScalar::new(pallas::Scalar::from_repr(repr).unwrap());
+        let (flipped_proof, flipped_a, _) = PastaProof::prove(
+            pallas_params,
+            vesta_params,
+            b"alice",
+            &flipped,
+            64,
+            &mut OsRng,
+        )
+        .unwrap();
+        assert_eq!(
+            flipped_proof.verify(pallas_params, vesta_params, b"alice", &flipped_a, &y_b),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn pallas_vesta_out_of_range() {
+        let (pallas_params, vesta_params) = (&*PALLAS_GROUP_PARAMS, &*VESTA_GROUP_PARAMS);
+        let x = SecretScalar::new((1u64 << 40).into());
+        assert_eq!(
+            PastaProof::prove(pallas_params, vesta_params, b"", &x, 32, &mut OsRng).unwrap_err(),
+            Error::InvalidWitness
+        );
+        assert!(matches!(
+            PastaProof::prove(pallas_params, vesta_params, b"", &x, 255, &mut OsRng),
+            Err(Error::InvalidRangeParams(_))
+        ));
+        assert_eq!(
+            PastaProof::prove(
+                pallas_params,
+                vesta_params,
+                b"",
+                &SecretScalar::new(pallas::Scalar::ZERO),
+                32,
+                &mut OsRng
+            )
+            .unwrap_err(),
+            Error::IdentityPoint
+        );
+        let full = SecretScalar::new(-pallas::Scalar::ONE);
+        assert_eq!(
+            embed_scalar::<_, vesta::Scalar>(&full, max_bits::<pallas::Point, vesta::Point>())
+                .unwrap_err(),
+            Error::InvalidWitness
+        );
+    }
+}
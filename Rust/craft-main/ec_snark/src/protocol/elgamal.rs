@@ -0,0 +1,325 @@
+use crate::common::{self, FromBytes, IntoBytes};
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::sigma::dleq::{Dleq, DleqStatement};
+use crate::protocol::sigma::{self, SigmaProof};
+use crate::protocol::GroupParams;
+use crate::secret::SecretScalar;
+use crate::Error;
+use rand_core::{CryptoRng, RngCore};
+use std::ops::Add;
+
+/// Domain-separation context of decryption proofs, see [`SecretKey::decrypt`].
+pub const DECRYPTION_CONTEXT: &[u8] = b"ec_snark.elgamal.decryption.v1";
+
+/// An ElGamal secret key `x`.
+#[derive(Debug)]
+pub struct SecretKey<G: CurveBackend> {
+    x: SecretScalar<G::Scalar>,
+}
+
+/// An ElGamal public key `y = g^x`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PublicKey<G> {
+    y: G,
+}
+
+/// An ElGamal ciphertext `(c1, c2) = (g^r, M * y^r)` of a group element `M`.
+///
+/// Ciphertexts under the same key are homomorphic: the sum of two ciphertexts
+/// decrypts to the sum of their plaintexts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Ciphertext<G> {
+    /// The ephemeral key `g^r`.
+    pub c1: G,
+    /// The masked plaintext `M * y^r`.
+    pub c2: G,
+}
+
+/// A decrypted plaintext with a Chaum–Pedersen proof that it is correct.
+///
+/// The proof shows `log_g(y) == log_c1(c2 / M)`, i.e. that `M` was obtained with
+/// the secret key of `y`, without revealing the key.
+#[derive(Clone, Debug)]
+pub struct Decryption<G: CurveBackend> {
+    /// The plaintext `M = c2 / c1^x`.
+    pub plaintext: G,
+    /// The DLEQ proof of correct decryption.
+    pub proof: SigmaProof<Dleq<G>>,
+}
+
+/// Generates a key pair for the generator `g` of `params`.
+pub fn keygen<G: CurveBackend, R: RngCore + CryptoRng>(
+    params: &GroupParams<G>,
+    rng: &mut R,
+) -> (SecretKey<G>, PublicKey<G>) {
+    let secret = SecretKey {
+        x: SecretScalar::random(rng),
+    };
+    let public = secret.public_key(params);
+    (secret, public)
+}
+
+impl<G: CurveBackend> SecretKey<G> {
+    /// Wraps an existing secret scalar.
+    pub fn new(x: SecretScalar<G::Scalar>) -> Self {
+        Self { x }
+    }
+
+    /// Computes the public key `y = g^x`.
+    pub fn public_key(&self, params: &GroupParams<G>) -> PublicKey<G> {
+        PublicKey {
+            y: params.g * self.x.expose_secret(),
+        }
+    }
+
+    /// Decrypts a ciphertext and proves that the plaintext is correct.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters of the curve.
+    /// * `context` - Application data the proof is bound to, e.g. an election identifier.
+    /// * `ciphertext` - The ciphertext to be decrypted.
+    /// * `rng` - The source of the prover's randomness.
+    ///
+    /// # Returns
+    /// The plaintext and its proof, or [`Error::IdentityPoint`] if the ciphertext
+    /// has an identity ephemeral key.
+    pub fn decrypt<R: RngCore + CryptoRng>(
+        &self,
+        params: &GroupParams<G>,
+        context: &[u8],
+        ciphertext: &Ciphertext<G>,
+        rng: &mut R,
+    ) -> Result<Decryption<G>, Error> {
+        let shared = ciphertext.c1 * self.x.expose_secret();
+        let plaintext = ciphertext.c2 - shared;
+        let statement =
+            decryption_statement(params, &self.public_key(params), ciphertext, &plaintext);
+        let proof = sigma::prove::<Dleq<G>, R>(
+            &[DECRYPTION_CONTEXT, context].concat(),
+            &statement,
+            &self.x,
+            rng,
+        )?;
+        Ok(Decryption { plaintext, proof })
+    }
+}
+
+impl<G: CurveBackend> PublicKey<G> {
+    /// Wraps a group element received from elsewhere, e.g. from storage.
+    pub fn from_point(y: G) -> Self {
+        Self { y }
+    }
+
+    /// The public key group element.
+    pub fn point(&self) -> G {
+        self.y
+    }
+
+    /// Encrypts the group element `m` with randomness drawn from `rng`.
+    pub fn encrypt<R: RngCore + CryptoRng>(
+        &self,
+        params: &GroupParams<G>,
+        m: &G,
+        rng: &mut R,
+    ) -> Ciphertext<G> {
+        let r = SecretScalar::<G::Scalar>::random(rng);
+        Ciphertext {
+            c1: params.g * r.expose_secret(),
+            c2: *m + self.y * r.expose_secret(),
+        }
+    }
+
+    /// Encrypts the scalar `v` "in the exponent", i.e. the group element `g^v`.
+    ///
+    /// Sums of such ciphertexts decrypt to `g^(sum v)`; small sums, such as vote
+    /// counts, can be recovered with [`discrete_log`].
+    pub fn encrypt_value<R: RngCore + CryptoRng>(
+        &self,
+        params: &GroupParams<G>,
+        v: &G::Scalar,
+        rng: &mut R,
+    ) -> Ciphertext<G> {
+        self.encrypt(params, &(params.g * v), rng)
+    }
+}
+
+impl<G: CurveBackend> Ciphertext<G> {
+    /// Returns a fresh-looking encryption of the same plaintext under `public_key`.
+    pub fn rerandomize<R: RngCore + CryptoRng>(
+        &self,
+        params: &GroupParams<G>,
+        public_key: &PublicKey<G>,
+        rng: &mut R,
+    ) -> Self {
+        *self + public_key.encrypt(params, &G::identity(), rng)
+    }
+}
+
+impl<G: CurveBackend> Add for Ciphertext<G> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            c1: self.c1 + rhs.c1,
+            c2: self.c2 + rhs.c2,
+        }
+    }
+}
+
+impl<G: CurveBackend> Decryption<G> {
+    /// Verifies that `plaintext` is the decryption of `ciphertext` under `public_key`.
+    ///
+    /// # Returns
+    /// Whether the proof is valid for the given context, or [`Error::IdentityPoint`]
+    /// if the key or the ciphertext is degenerate.
+    pub fn verify(
+        &self,
+        params: &GroupParams<G>,
+        context: &[u8],
+        public_key: &PublicKey<G>,
+        ciphertext: &Ciphertext<G>,
+    ) -> Result<bool, Error> {
+        let statement = decryption_statement(params, public_key, ciphertext, &self.plaintext);
+        sigma::verify(
+            &[DECRYPTION_CONTEXT, context].concat(),
+            &statement,
+            &self.proof,
+        )
+    }
+}
+
+/// The statement `log_g(y) == log_c1(c2 / M)` of a correct decryption.
+fn decryption_statement<G: CurveBackend>(
+    params: &GroupParams<G>,
+    public_key: &PublicKey<G>,
+    ciphertext: &Ciphertext<G>,
+    plaintext: &G,
+) -> DleqStatement<G> {
+    DleqStatement {
+        g: params.g,
+        h: ciphertext.c1,
+        y1: public_key.y,
+        y2: ciphertext.c2 - plaintext,
+    }
+}
+
+/// Finds `v` in `[0, max]` with `g^v == m` by exhaustive search.
+///
+/// Recovers the plaintext of [`PublicKey::encrypt_value`] when it is known to be
+/// small, e.g. a tally of votes.
+pub fn discrete_log<G: CurveBackend>(params: &GroupParams<G>, m: &G, max: u64) -> Option<u64> {
+    let mut acc = G::identity();
+    for v in 0..=max {
+        if acc == *m {
+            return Some(v);
+        }
+        acc += params.g;
+    }
+    None
+}
+
+impl<G: CurveBackend> IntoBytes<Ciphertext<G>> for Ciphertext<G> {
+    fn to(t: &Ciphertext<G>) -> Vec<u8> {
+        common::encode_parts(&[G::to(&t.c1), G::to(&t.c2)])
+    }
+}
+
+impl<G: CurveBackend + FromBytes<G>> FromBytes<Ciphertext<G>> for Ciphertext<G> {
+    fn from(bytes: &[u8]) -> Result<Ciphertext<G>, Error> {
+        let parts = common::decode_parts(bytes, 2)?;
+        Ok(Ciphertext {
+            c1: <G as FromBytes<G>>::from(parts[0])?,
+            c2: <G as FromBytes<G>>::from(parts[1])?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::constants::{PALLAS_GROUP_PARAMS, RISTRETTO_GROUP_PARAMS};
+    use curve25519_dalek::ristretto::RistrettoPoint;
+    use pasta_curves::group::ff::Field;
+    use pasta_curves::pallas;
+    use rand_core::OsRng;
+
+    fn verifiable_decryption<G: CurveBackend>(params: &GroupParams<G>) {
+        let (secret, public) = keygen(params, &mut OsRng);
+        let m = G::hash_to_group("test", b"message");
+        let ciphertext = public.encrypt(params, &m, &mut OsRng);
+
+        let decryption = secret
+            .decrypt(params, b"poll", &ciphertext, &mut OsRng)
+            .unwrap();
+        assert_eq!(decryption.plaintext, m);
+        assert_eq!(
+            decryption.verify(params, b"poll", &public, &ciphertext),
+            Ok(true)
+        );
+        assert_eq!(
+            decryption.verify(params, b"other", &public, &ciphertext),
+            Ok(false)
+        );
+
+        let mut forged = decryption.clone();
+        forged.plaintext += params.g;
+        assert_eq!(
+            forged.verify(params, b"poll", &public, &ciphertext),
+            Ok(false)
+        );
+
+        let (_, other) = keygen(params, &mut OsRng);
+        assert_eq!(
+            decryption.verify(params, b"poll", &other, &ciphertext),
+            Ok(false)
+        );
+
+        let rerandomized = ciphertext.rerandomize(params, &public, &mut OsRng);
+        assert_ne!(rerandomized, ciphertext);
+        let decryption = secret
+            .decrypt(params, b"poll", &rerandomized, &mut OsRng)
+            .unwrap();
+        assert_eq!(decryption.plaintext, m);
+    }
+
+    fn homomorphic_tally<G: CurveBackend>(params: &GroupParams<G>) {
+        let (secret, public) = keygen(params, &mut OsRng);
+        let votes = [1u64, 0, 1, 1, 0, 1];
+        let tally = votes
+            .iter()
+            .map(|v| public.encrypt_value(params, &G::Scalar::from(*v), &mut OsRng))
+            .reduce(|a, b| a + b)
+            .unwrap();
+        let decryption = secret.decrypt(params, b"poll", &tally, &mut OsRng).unwrap();
+        assert_eq!(
+            decryption.verify(params, b"poll", &public, &tally),
+            Ok(true)
+        );
+        assert_eq!(discrete_log(params, &decryption.plaintext, 10), Some(4));
+        assert_eq!(discrete_log(params, &decryption.plaintext, 3), None);
+    }
+
+    #[test]
+    fn pallas_elgamal() {
+        verifiable_decryption::<pallas::Point>(&PALLAS_GROUP_PARAMS);
+        homomorphic_tally::<pallas::Point>(&PALLAS_GROUP_PARAMS);
+    }
+
+    #[test]
+    fn ristretto_elgamal() {
+        verifiable_decryption::<RistrettoPoint>(&RISTRETTO_GROUP_PARAMS);
+        homomorphic_tally::<RistrettoPoint>(&RISTRETTO_GROUP_PARAMS);
+    }
+
+    #[test]
+    fn ciphertext_round_trips_through_bytes() {
+        let params = &*PALLAS_GROUP_PARAMS;
+        let (_, public) = keygen(params, &mut OsRng);
+        let ciphertext = public.encrypt_value(params, &pallas::Scalar::ONE, &mut OsRng);
+        let bytes = Ciphertext::to(&ciphertext);
+        assert_eq!(
+            <Ciphertext<pallas::Point> as FromBytes<_>>::from(&bytes),
+            Ok(ciphertext)
+        );
+    }
+}
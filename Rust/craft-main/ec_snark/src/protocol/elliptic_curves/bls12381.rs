@@ -0,0 +1,113 @@
+use crate::common::EllipticCurve;
+use crate::protocol::constants::BLS12_381_GROUP_PARAMS;
+use crate::protocol::elliptic_curves::dleq::DleqEllipticCurve;
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::GroupParams;
+use bls12_381::{G1Affine, Scalar};
+use sha2::{Digest, Sha512};
+
+/// The G1 group of BLS12-381, i.e. `<bls12_381::Bls12 as pairing::Engine>::G1`.
+pub type Point = bls12_381::G1Projective;
+
+/// The DLEQ protocol on the G1 group of BLS12-381.
+pub type Bls12381EllipticCurve = DleqEllipticCurve<Point>;
+
+impl CurveBackend for Point {
+    const CURVE: EllipticCurve = EllipticCurve::Bls12381;
+    const FIELD_MODULUS: &'static str =
+        "0x1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab";
+    const COFACTOR: &'static str = "0x396c8c005555e1568c00aaab0000aaab";
+
+    fn group_params() -> GroupParams<Point> {
+        BLS12_381_GROUP_PARAMS.to_owned()
+    }
+
+    fn is_torsion_free(&self) -> bool {
+        bool::from(G1Affine::from(self).is_torsion_free())
+    }
+
+    fn scalar_from_uniform_bytes(bytes: &[u8; 64]) -> Scalar {
+        Scalar::from_bytes_wide(bytes)
+    }
+
+    fn hash_to_group(domain: &str, message: &[u8]) -> Point {
+        hash_to_g1(domain, message)
+    }
+}
+
+impl_curve_codecs!(Point, Scalar);
+
+/// Deterministically hashes a message to a G1 element with unknown discrete logarithm.
+///
+/// `SHA-512(len(domain) || domain || message || counter)` is truncated to a compressed
+/// x-coordinate for `counter = 0, 1, ...` until it decodes to a curve point, whose
+/// cofactor is then cleared.
+pub fn hash_to_g1(domain: &str, message: &[u8]) -> Point {
+    for counter in 0u32.. {
+        let mut hasher = Sha512::new();
+        hasher.update((domain.len() as u64).to_le_bytes());
+        hasher.update(domain);
+        hasher.update(message);
+        hasher.update(counter.to_be_bytes());
+        let digest = hasher.finalize();
+
+        let mut bytes = [0u8; 48];
+        bytes.copy_from_slice(&digest[..48]);
+        // Keep only the compression flag out of the three flag bits.
+        bytes[0] = (bytes[0] & 0x1f) | 0x80;
+
+        let candidate: Option<G1Affine> = G1Affine::from_compressed_unchecked(&bytes).into();
+        if let Some(point) = candidate {
+            let point = Point::from(point).clear_cofactor();
+            if !bool::from(point.is_identity()) {
+                return point;
+            }
+        }
+    }
+    unreachable!("a valid x-coordinate is found with overwhelming probability")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{FromBytes, IntoBytes, Random};
+    use bls12_381::{Bls12, G2Projective};
+    use pairing::Engine;
+    use pasta_curves::group::Curve;
+
+    #[test]
+    fn bls12381_point_serialization() {
+        let original = <Point as Random<Point>>::random().unwrap();
+        let bytes = <Point as IntoBytes<_>>::to(&original);
+        assert_eq!(bytes.len(), 48);
+        let recovered = <Point as FromBytes<Point>>::from(&bytes).unwrap();
+        assert_eq!(original, recovered);
+    }
+
+    #[test]
+    fn bls12381_scalar_serialization() {
+        let original = <Scalar as Random<Scalar>>::random().unwrap();
+        let bytes = <Scalar as IntoBytes<_>>::to(&original);
+        let recovered = <Scalar as FromBytes<Scalar>>::from(&bytes).unwrap();
+        assert_eq!(original, recovered);
+    }
+
+    #[test]
+    fn bls12381_group_params() {
+        assert_eq!(Point::group_params().g, BLS12_381_GROUP_PARAMS.g);
+    }
+
+    #[test]
+    fn bls12381_pairing_of_dleq_statement() {
+        // `y1 = g^x` can be checked against `G2^x` with a pairing, which is what
+        // pairing-based extensions of the protocol build on.
+        let x = <Scalar as Random<Scalar>>::random().unwrap();
+        let params = Point::group_params();
+        let y1 = params.g * x;
+        let g2 = G2Projective::generator();
+        assert_eq!(
+            Bls12::pairing(&y1.to_affine(), &g2.to_affine()),
+            Bls12::pairing(&params.g.to_affine(), &(g2 * x).to_affine())
+        );
+    }
+}
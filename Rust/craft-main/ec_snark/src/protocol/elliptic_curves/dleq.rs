@@ -0,0 +1,296 @@
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::fiat_shamir::{dleq_challenge, DleqProof, NonInteractiveProtocol};
+use crate::protocol::msm::multiscalar_mul;
+use crate::protocol::sigma::dleq::{Dleq, DleqStatement};
+use crate::protocol::sigma::SigmaProtocol;
+use crate::protocol::{GroupParams, Protocol};
+use crate::secret::{Nonce, SecretScalar};
+use crate::Error;
+use pasta_curves::group::ff::Field;
+use rand_core::{CryptoRng, OsRng, RngCore};
+use std::marker::PhantomData;
+
+/// The equality-of-discrete-logarithms protocol over any prime-order group.
+///
+/// Proving and verifying single transcripts is delegated to [`Dleq`], i.e. the
+/// [`Linear`](crate::protocol::sigma::linear::Linear) proof of
+/// `PK{(x): y1 = x·g ∧ y2 = x·h}` for the generators of the group parameters.
+///
+/// Concrete curves are type aliases of this struct, e.g.
+/// [`PallasEllipticCurve`](super::pallas::PallasEllipticCurve).
+pub struct DleqEllipticCurve<G> {
+    _group: PhantomData<G>,
+}
+
+/// An accepting transcript `((y1, y2, r1, r2), c, s)` of the interactive protocol.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DleqTranscript<G: CurveBackend> {
+    /// The commitment parameters `(y1, y2, r1, r2)`.
+    pub commit_parameters: (G, G, G, G),
+    /// The challenge `c`.
+    pub challenge: G::Scalar,
+    /// The response `s`.
+    pub response: G::Scalar,
+}
+
+impl<G: CurveBackend> DleqEllipticCurve<G> {
+    /// Produces an accepting transcript for `(y1, y2)` without knowing `x`.
+    ///
+    /// This is the honest-verifier zero-knowledge simulator: it draws `c` and `s`
+    /// first and solves for `r1 = g^s / y1^c` and `r2 = h^s / y2^c`. The transcripts
+    /// are distributed exactly like those of an honest prover and verifier, which is
+    /// why a transcript on its own convinces nobody but the verifier who chose `c`
+    /// after seeing `r1` and `r2`. Useful for tests and demo fixtures.
+    ///
+    /// # Returns
+    /// The transcript, or [`Error::IdentityPoint`] if `y1` or `y2` is the identity.
+    pub fn simulate_with_rng<R: RngCore + CryptoRng>(
+        params: &GroupParams<G>,
+        y1: &G,
+        y2: &G,
+        rng: &mut R,
+    ) -> Result<DleqTranscript<G>, Error> {
+        let c = <G::Scalar as Field>::random(&mut *rng);
+        let ((r1, r2), s) = Dleq::simulate(&statement(params, y1, y2), &c, rng)?;
+        Ok(DleqTranscript {
+            commit_parameters: (*y1, *y2, r1, r2),
+            challenge: c,
+            response: s,
+        })
+    }
+
+    /// Produces an accepting transcript with randomness from `OsRng`.
+    ///
+    /// See [`DleqEllipticCurve::simulate_with_rng`].
+    pub fn simulate(params: &GroupParams<G>, y1: &G, y2: &G) -> Result<DleqTranscript<G>, Error> {
+        Self::simulate_with_rng(params, y1, y2, &mut OsRng)
+    }
+}
+
+/// The DLEQ statement of `(y1, y2)` for the generators of `params`.
+fn statement<G: CurveBackend>(params: &GroupParams<G>, y1: &G, y2: &G) -> DleqStatement<G> {
+    DleqStatement {
+        g: params.g,
+        h: params.h,
+        y1: *y1,
+        y2: *y2,
+    }
+}
+
+/// Rejects commitment parameters containing the identity, for which the
+/// verification equations hold trivially.
+fn check_commit_parameters<G: CurveBackend>(cp: &(G, G, G, G)) -> Result<(), Error> {
+    let (y1, y2, r1, r2) = cp;
+    if [y1, y2, r1, r2].iter().any(|p| bool::from(p.is_identity())) {
+        return Err(Error::IdentityPoint);
+    }
+    Ok(())
+}
+
+impl<G> Protocol for DleqEllipticCurve<G>
+where
+    G: CurveBackend,
+{
+    type Secret = SecretScalar<G::Scalar>;
+    type Response = G::Scalar;
+    type Challenge = G::Scalar;
+    type CommitmentRandom = Nonce<G::Scalar>;
+    type GroupParameters = GroupParams<G>;
+    type CommitParameters = (G, G, G, G);
+
+    /// Generates a commitment to a secret.
+    ///
+    /// # Parameters
+    ///
+    /// * `params` - Group parameters of the curve.
+    /// * `x` - The secret scalar value to which the commitment is made.
+    /// * `rng` - The source of the commitment random scalar.
+    ///
+    /// # Returns
+    ///
+    /// Returns a tuple containing the commitment parameters and a commitment random scalar,
+    /// or [`Error::IdentityPoint`] if the secret is zero.
+    fn commitment_with_rng<R: RngCore + CryptoRng>(
+        params: &Self::GroupParameters,
+        x: &Self::Secret,
+        rng: &mut R,
+    ) -> Result<(Self::CommitParameters, Self::CommitmentRandom), Error>
+    where
+        Self: Sized,
+    {
+        let statement = DleqStatement::new(params, x);
+        let ((r1, r2), k) = Dleq::commit(&statement, x, rng)?;
+        Ok(((statement.y1, statement.y2, r1, r2), k))
+    }
+
+    /// Generates a random challenge scalar.
+    ///
+    /// # Parameters
+    ///
+    /// * `_params` - Ignored in this implementation. Group parameters can be used if needed.
+    /// * `rng` - The source of the challenge.
+    ///
+    /// # Returns
+    ///
+    /// Returns a random scalar value to be used as a challenge.
+    fn challenge_with_rng<R: RngCore + CryptoRng>(
+        _: &GroupParams<G>,
+        rng: &mut R,
+    ) -> Self::Challenge {
+        <G::Scalar as Field>::random(rng)
+    }
+
+    /// Generates a response to a challenge given a secret and a random scalar.
+    ///
+    /// # Parameters
+    ///
+    /// * `params` - Group parameters of the curve.
+    /// * `k` - The nonce used during commitment, consumed by the response.
+    /// * `c` - The challenge scalar.
+    /// * `x` - The secret scalar.
+    ///
+    /// # Returns
+    ///
+    /// Returns the response scalar, which is calculated as `k + (c * x)`.
+    fn challenge_response(
+        params: &Self::GroupParameters,
+        k: Self::CommitmentRandom,
+        c: &Self::Challenge,
+        x: &Self::Secret,
+    ) -> Self::Response
+    where
+        Self: Sized,
+    {
+        // The one-scalar witness always matches the one-nonce state.
+        Dleq::respond(&DleqStatement::new(params, x), x, k, c).unwrap()
+    }
+
+    /// Verifies the correctness of the response to a challenge.
+    ///
+    /// # Parameters
+    ///
+    /// * `params` - Group parameters of the curve.
+    /// * `s` - The response scalar.
+    /// * `c` - The challenge scalar.
+    /// * `cp` - The commitment parameters tuple.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the verification is successful, `false` otherwise, or
+    /// [`Error::IdentityPoint`] if one of the commitment parameters is the identity.
+    fn verify(
+        params: &Self::GroupParameters,
+        s: &Self::Response,
+        c: &Self::Challenge,
+        cp: &Self::CommitParameters,
+    ) -> Result<bool, Error> {
+        let (y1, y2, r1, r2) = cp;
+        Dleq::verify(&statement(params, y1, y2), &(*r1, *r2), c, s)
+    }
+
+    /// Verifies many transcripts with a single multiscalar multiplication.
+    ///
+    /// Both verification equations of every transcript are weighted with fresh random
+    /// scalars `a_i`, `b_i` and summed up, so that
+    /// `g^(sum a_i s_i) h^(sum b_i s_i) == prod r1_i^a_i y1_i^(a_i c_i) r2_i^b_i y2_i^(b_i c_i)`
+    /// holds for an invalid batch only with negligible probability. If the combined
+    /// check fails, or a transcript contains the identity, every transcript is verified
+    /// on its own to find the invalid ones.
+    ///
+    /// # Parameters
+    ///
+    /// * `params` - Group parameters of the curve.
+    /// * `transcripts` - Tuples of the response, the challenge and the commitment parameters.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if every transcript is valid, otherwise the indices of the invalid ones.
+    fn verify_batch(
+        params: &Self::GroupParameters,
+        transcripts: &[(Self::Response, Self::Challenge, Self::CommitParameters)],
+    ) -> Result<(), Vec<usize>> {
+        let mut rng = OsRng;
+        let mut scalars = Vec::with_capacity(4 * transcripts.len() + 2);
+        let mut points = Vec::with_capacity(4 * transcripts.len() + 2);
+        let mut g_scalar = G::Scalar::ZERO;
+        let mut h_scalar = G::Scalar::ZERO;
+
+        for (s, c, (y1, y2, r1, r2)) in transcripts {
+            let a = <G::Scalar as Field>::random(&mut rng);
+            let b = <G::Scalar as Field>::random(&mut rng);
+            g_scalar += a * s;
+            h_scalar += b * s;
+            scalars.extend([-a, -(a * c), -b, -(b * c)]);
+            points.extend([*r1, *y1, *r2, *y2]);
+        }
+        scalars.extend([g_scalar, h_scalar]);
+        points.extend([params.g, params.h]);
+
+        let well_formed = transcripts
+            .iter()
+            .all(|(_, _, cp)| check_commit_parameters(cp).is_ok());
+        if well_formed && bool::from(multiscalar_mul(&scalars, &points).is_identity()) {
+            return Ok(());
+        }
+
+        let invalid: Vec<usize> = transcripts
+            .iter()
+            .enumerate()
+            .filter(|(_, (s, c, cp))| Self::verify(params, s, c, cp) != Ok(true))
+            .map(|(i, _)| i)
+            .collect();
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid)
+        }
+    }
+}
+
+impl<G> NonInteractiveProtocol for DleqEllipticCurve<G>
+where
+    G: CurveBackend,
+{
+    type Proof = DleqProof<G, G::Scalar>;
+
+    /// Generates a non-interactive proof of knowledge of a secret.
+    ///
+    /// # Parameters
+    ///
+    /// * `params` - Group parameters of the curve.
+    /// * `x` - The secret scalar value.
+    /// * `rng` - The source of the commitment random scalar.
+    ///
+    /// # Returns
+    ///
+    /// Returns a proof whose challenge is derived from the transcript hash, or
+    /// [`Error::IdentityPoint`] if the secret is zero.
+    fn prove_with_rng<R: RngCore + CryptoRng>(
+        params: &Self::GroupParameters,
+        x: &Self::Secret,
+        rng: &mut R,
+    ) -> Result<Self::Proof, Error> {
+        let (cp, k) = Self::commitment_with_rng(params, x, rng)?;
+        let c = dleq_challenge(params, &cp);
+        let s = Self::challenge_response(params, k, &c, x);
+        let (y1, y2, r1, r2) = cp;
+        Ok(DleqProof { y1, y2, r1, r2, s })
+    }
+
+    /// Verifies a non-interactive proof.
+    ///
+    /// # Parameters
+    ///
+    /// * `params` - Group parameters of the curve.
+    /// * `proof` - The proof to be verified.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the verification is successful, `false` otherwise, or
+    /// [`Error::IdentityPoint`] if the proof contains the identity.
+    fn verify_proof(params: &Self::GroupParameters, proof: &Self::Proof) -> Result<bool, Error> {
+        let cp = (proof.y1, proof.y2, proof.r1, proof.r2);
+        let c = dleq_challenge(params, &cp);
+        Self::verify(params, &proof.s, &c, &cp)
+    }
+}
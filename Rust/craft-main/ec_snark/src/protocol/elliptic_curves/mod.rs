@@ -1,2 +1,139 @@
+use crate::common::{EllipticCurve, IntoBytes};
+use crate::protocol::GroupParams;
+use pasta_curves::group::ff::PrimeFieldBits;
+use pasta_curves::group::{Group, GroupEncoding};
+use sha2::{Digest, Sha512};
+
+/// A prime-order group the DLEQ protocol can be instantiated over.
+///
+/// Adding a curve takes a type alias of [`dleq::DleqEllipticCurve`], an
+/// implementation of this trait and an `impl_curve_codecs!` invocation.
+pub trait CurveBackend: Group<Scalar: PrimeFieldBits> + GroupEncoding + IntoBytes<Self> {
+    /// The curve this group belongs to, absorbed into Fiat–Shamir transcripts.
+    const CURVE: EllipticCurve;
+
+    /// The modulus of the base field, in the `0x`-prefixed hex format of `PrimeField::MODULUS`.
+    const FIELD_MODULUS: &'static str;
+
+    /// The cofactor of the group in the group of curve points, in the same format.
+    const COFACTOR: &'static str;
+
+    /// Returns the group parameters shipped for this curve.
+    fn group_params() -> GroupParams<Self>;
+
+    /// Returns whether the point lies in the prime-order group, i.e. has no torsion component.
+    fn is_torsion_free(&self) -> bool;
+
+    /// Reduces 64 uniformly random bytes to a scalar, with negligible bias.
+    fn scalar_from_uniform_bytes(bytes: &[u8; 64]) -> Self::Scalar;
+
+    /// Hashes a message to a group element whose discrete logarithm is unknown.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain-separation prefix of the hash function.
+    /// * `message` - The message to be hashed.
+    fn hash_to_group(domain: &str, message: &[u8]) -> Self;
+
+    /// Hashes a message of any length to a scalar, e.g. to derive a secret from a password.
+    ///
+    /// Reduces `SHA-512(len(domain) || domain || message)` with
+    /// [`CurveBackend::scalar_from_uniform_bytes`]. Scalars received from a peer must be
+    /// decoded with the strict `FromBytes` instead.
+    ///
+    /// # Arguments
+    /// * `domain` - Domain-separation prefix of the hash function.
+    /// * `message` - The message to be hashed.
+    fn hash_to_scalar(domain: &str, message: &[u8]) -> Self::Scalar {
+        let mut hasher = Sha512::new();
+        hasher.update((domain.len() as u64).to_le_bytes());
+        hasher.update(domain);
+        hasher.update(message);
+        Self::scalar_from_uniform_bytes(&hasher.finalize().into())
+    }
+}
+
+/// Implements `IntoBytes`, `FromBytes` and `Random` for a point type and its scalar field.
+macro_rules! impl_curve_codecs {
+    ($point:ty, $scalar:ty) => {
+        impl $crate::common::IntoBytes<$point> for $point {
+            fn to(t: &$point) -> Vec<u8> {
+                pasta_curves::group::GroupEncoding::to_bytes(t)
+                    .as_ref()
+                    .to_vec()
+            }
+        }
+
+        impl $crate::common::FromBytes<$point> for $point {
+            /// Accepts only the canonical encoding of a non-identity point.
+            fn from(bytes: &[u8]) -> Result<$point, $crate::Error> {
+                let mut repr = <$point as pasta_curves::group::GroupEncoding>::Repr::default();
+                if bytes.len() != repr.as_ref().len() {
+                    return Err($crate::Error::InvalidLength {
+                        expected: repr.as_ref().len(),
+                        actual: bytes.len(),
+                    });
+                }
+                repr.as_mut().copy_from_slice(bytes);
+                let point: $point = Option::from(
+                    <$point as pasta_curves::group::GroupEncoding>::from_bytes(&repr),
+                )
+                .ok_or($crate::Error::OffCurvePoint)?;
+                if pasta_curves::group::GroupEncoding::to_bytes(&point).as_ref() != bytes {
+                    return Err($crate::Error::NonCanonicalEncoding);
+                }
+                if bool::from(pasta_curves::group::Group::is_identity(&point)) {
+                    return Err($crate::Error::IdentityPoint);
+                }
+                Ok(point)
+            }
+        }
+
+        impl $crate::common::IntoBytes<$scalar> for $scalar {
+            fn to(t: &$scalar) -> Vec<u8> {
+                pasta_curves::group::ff::PrimeField::to_repr(t)
+                    .as_ref()
+                    .to_vec()
+            }
+        }
+
+        impl $crate::common::FromBytes<$scalar> for $scalar {
+            /// Accepts only the canonical `to_repr` encoding of a scalar.
+            fn from(bytes: &[u8]) -> Result<$scalar, $crate::Error> {
+                let mut repr = <$scalar as pasta_curves::group::ff::PrimeField>::Repr::default();
+                if bytes.len() != repr.as_ref().len() {
+                    return Err($crate::Error::InvalidLength {
+                        expected: repr.as_ref().len(),
+                        actual: bytes.len(),
+                    });
+                }
+                repr.as_mut().copy_from_slice(bytes);
+                Option::from(<$scalar as pasta_curves::group::ff::PrimeField>::from_repr(
+                    repr,
+                ))
+                .ok_or($crate::Error::NonCanonicalEncoding)
+            }
+        }
+
+        impl $crate::common::Random<$point> for $point {
+            fn random() -> Result<$point, $crate::Error> {
+                Ok(<$point as pasta_curves::group::Group>::random(
+                    &mut rand_core::OsRng,
+                ))
+            }
+        }
+
+        impl $crate::common::Random<$scalar> for $scalar {
+            fn random() -> Result<$scalar, $crate::Error> {
+                Ok(<$scalar as pasta_curves::group::ff::Field>::random(
+                    &mut rand_core::OsRng,
+                ))
+            }
+        }
+    };
+}
+
+pub mod bls12381;
+pub mod dleq;
 pub mod pallas;
+pub mod ristretto;
 pub mod vesta;
@@ -0,0 +1,73 @@
+use crate::common::EllipticCurve;
+use crate::protocol::constants::RISTRETTO_GROUP_PARAMS;
+use crate::protocol::elliptic_curves::dleq::DleqEllipticCurve;
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::GroupParams;
+use curve25519_dalek::ristretto::RistrettoPoint as Point;
+use curve25519_dalek::scalar::Scalar;
+use pasta_curves::group::ff::FromUniformBytes;
+use sha2::{Digest, Sha512};
+
+/// The DLEQ protocol on the Ristretto255 group.
+pub type RistrettoEllipticCurve = DleqEllipticCurve<Point>;
+
+impl CurveBackend for Point {
+    const CURVE: EllipticCurve = EllipticCurve::Ristretto;
+    const FIELD_MODULUS: &'static str =
+        "0x7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed";
+    /// Ristretto255 itself has prime order; the cofactor 8 of Curve25519 is
+    /// quotiented out by the encoding.
+    const COFACTOR: &'static str = "0x1";
+
+    fn group_params() -> GroupParams<Point> {
+        RISTRETTO_GROUP_PARAMS.to_owned()
+    }
+
+    /// Ristretto255 is a prime-order quotient of Curve25519, so every element is
+    /// torsion-free even though the underlying curve has cofactor 8.
+    fn is_torsion_free(&self) -> bool {
+        true
+    }
+
+    fn scalar_from_uniform_bytes(bytes: &[u8; 64]) -> Scalar {
+        Scalar::from_uniform_bytes(bytes)
+    }
+
+    /// Maps `SHA-512(len(domain) || domain || message)` to the group with Elligator.
+    fn hash_to_group(domain: &str, message: &[u8]) -> Point {
+        let mut hasher = Sha512::new();
+        hasher.update((domain.len() as u64).to_le_bytes());
+        hasher.update(domain);
+        hasher.update(message);
+        Point::from_hash(hasher)
+    }
+}
+
+impl_curve_codecs!(Point, Scalar);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{FromBytes, IntoBytes, Random};
+
+    #[test]
+    fn ristretto_point_serialization() {
+        let original = <Point as Random<Point>>::random().unwrap();
+        let bytes = <Point as IntoBytes<_>>::to(&original);
+        let recovered = <Point as FromBytes<Point>>::from(&bytes).unwrap();
+        assert_eq!(original, recovered);
+    }
+
+    #[test]
+    fn ristretto_scalar_serialization() {
+        let original = <Scalar as Random<Scalar>>::random().unwrap();
+        let bytes = <Scalar as IntoBytes<_>>::to(&original);
+        let recovered = <Scalar as FromBytes<Scalar>>::from(&bytes).unwrap();
+        assert_eq!(original, recovered);
+    }
+
+    #[test]
+    fn ristretto_group_params() {
+        assert_eq!(Point::group_params().g, RISTRETTO_GROUP_PARAMS.g);
+    }
+}
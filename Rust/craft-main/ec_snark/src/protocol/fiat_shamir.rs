@@ -0,0 +1,119 @@
+use crate::common::{self, FromBytes, IntoBytes};
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::transcript::Transcript;
+use crate::protocol::{GroupParams, Protocol};
+use crate::Error;
+use rand_core::{CryptoRng, OsRng, RngCore};
+
+/// Domain-separation label of the non-interactive DLEQ proof.
+pub const DLEQ_DOMAIN: &[u8] = b"ec_snark.dleq.v1";
+
+/// A self-contained, non-interactive proof that `log_g(y1) == log_h(y2)`.
+///
+/// The challenge is not stored: the verifier recomputes it from the statement
+/// and the commitments, so the proof can be stored, forwarded and checked offline.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DleqProof<P, S> {
+    /// The public value `y1 = g^x`.
+    pub y1: P,
+    /// The public value `y2 = h^x`.
+    pub y2: P,
+    /// The commitment `r1 = g^k`.
+    pub r1: P,
+    /// The commitment `r2 = h^k`.
+    pub r2: P,
+    /// The response `s = k + c * x`.
+    pub s: S,
+}
+
+/// A trait extending [`Protocol`] with its Fiat–Shamir transformed variant.
+///
+/// The verifier's random challenge is replaced by a hash of the transcript, which
+/// removes the round trip between commitment and response.
+pub trait NonInteractiveProtocol: Protocol {
+    type Proof;
+
+    /// Produces a non-interactive proof of knowledge of `x`, drawing the commitment
+    /// randomness from `rng`.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters used in the protocol.
+    /// * `x` - The secret value the proof is made for.
+    /// * `rng` - The source of the commitment randomness.
+    ///
+    /// # Returns
+    /// A proof carrying the statement, the commitments and the response,
+    /// or an error if the statement would be trivial.
+    fn prove_with_rng<R: RngCore + CryptoRng>(
+        params: &Self::GroupParameters,
+        x: &Self::Secret,
+        rng: &mut R,
+    ) -> Result<Self::Proof, Error>
+    where
+        Self: Sized;
+
+    /// Produces a non-interactive proof of knowledge of `x` with randomness from `OsRng`.
+    ///
+    /// See [`NonInteractiveProtocol::prove_with_rng`].
+    fn prove(params: &Self::GroupParameters, x: &Self::Secret) -> Result<Self::Proof, Error>
+    where
+        Self: Sized,
+    {
+        Self::prove_with_rng(params, x, &mut OsRng)
+    }
+
+    /// Verifies a non-interactive proof.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters used in the protocol.
+    /// * `proof` - The proof to be verified.
+    ///
+    /// # Returns
+    /// A boolean indicating whether the verification was successful,
+    /// or an error if the proof is malformed.
+    fn verify_proof(params: &Self::GroupParameters, proof: &Self::Proof) -> Result<bool, Error>
+    where
+        Self: Sized;
+}
+
+/// Computes the Fiat–Shamir challenge of a DLEQ transcript.
+///
+/// The domain label, the curve name, both generators, the statement `(y1, y2)`
+/// and the commitments `(r1, r2)` are absorbed in this order.
+pub fn dleq_challenge<G: CurveBackend>(params: &GroupParams<G>, cp: &(G, G, G, G)) -> G::Scalar {
+    let (y1, y2, r1, r2) = cp;
+    let mut transcript = Transcript::new(DLEQ_DOMAIN);
+    transcript.append_message(b"curve", G::CURVE.to_string().as_bytes());
+    transcript.append_element(b"g", &params.g);
+    transcript.append_element(b"h", &params.h);
+    transcript.append_element(b"y1", y1);
+    transcript.append_element(b"y2", y2);
+    transcript.append_element(b"r1", r1);
+    transcript.append_element(b"r2", r2);
+    transcript.challenge_scalar::<G>(b"c")
+}
+
+impl<P: IntoBytes<P>, S: IntoBytes<S>> IntoBytes<DleqProof<P, S>> for DleqProof<P, S> {
+    fn to(t: &DleqProof<P, S>) -> Vec<u8> {
+        common::encode_parts(&[
+            P::to(&t.y1),
+            P::to(&t.y2),
+            P::to(&t.r1),
+            P::to(&t.r2),
+            S::to(&t.s),
+        ])
+    }
+}
+
+impl<P: FromBytes<P>, S: FromBytes<S>> FromBytes<DleqProof<P, S>> for DleqProof<P, S> {
+    fn from(bytes: &[u8]) -> Result<DleqProof<P, S>, Error> {
+        let parts = common::decode_parts(bytes, 5)?;
+        Ok(DleqProof {
+            y1: P::from(parts[0])?,
+            y2: P::from(parts[1])?,
+            r1: P::from(parts[2])?,
+            r2: P::from(parts[3])?,
+            s: S::from(parts[4])?,
+        })
+    }
+}
@@ -0,0 +1,401 @@
+use crate::common::{FromBytes, IntoBytes};
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::Protocol;
+use crate::secret::{Nonce, SecretScalar};
+use crate::Error;
+use bellman::groth16::{self, Parameters, PreparedVerifyingKey, Proof};
+use bellman::{Circuit, ConstraintSystem, LinearCombination, SynthesisError, VerificationError};
+use bls12_381::{Bls12, G1Projective, Scalar};
+use lazy_static::lazy_static;
+use pasta_curves::group::ff::Field;
+use rand_core::{CryptoRng, RngCore};
+
+/// The number of rounds of the MiMC permutation. The cube-based Feistel mode needs
+/// `2 * ceil(log_3(r))` rounds, and `ceil(log_3(r)) = 161` for the 255-bit scalar
+/// field of BLS12-381.
+pub const MIMC_ROUNDS: usize = 322;
+
+/// Domain separator the MiMC round constants are derived from.
+const MIMC_DOMAIN: &str = "ec_snark.groth16.mimc.v1";
+
+/// The length of an encoded proof: two compressed G1 points and one compressed G2 point.
+pub const PROOF_LEN: usize = 192;
+
+/// The number of public inputs of [`PreimageCircuit`]: the commitment and the nonce.
+const PUBLIC_INPUTS: usize = 2;
+
+lazy_static! {
+    /// The round constants `C_i = H(i)` of the MiMC permutation.
+    static ref MIMC_CONSTANTS: Vec<Scalar> = (0..MIMC_ROUNDS as u32)
+        .map(|i| G1Projective::hash_to_scalar(MIMC_DOMAIN, &i.to_le_bytes()))
+        .collect();
+}
+
+/// Hashes a scalar with MiMC in Feistel mode, `x_L = x`, `x_R = 0`, and the
+/// round function `(x_L + C_i)^3`.
+///
+/// This is the commitment a user registers for the Groth16 login scheme.
+pub fn mimc(x: &Scalar) -> Scalar {
+    let (mut xl, mut xr) = (*x, Scalar::ZERO);
+    for c in MIMC_CONSTANTS.iter() {
+        let t = xl + c;
+        (xl, xr) = (xr + t.square() * t, xl);
+    }
+    xl
+}
+
+/// The circuit proving knowledge of `x` with `mimc(x) == commitment`.
+///
+/// The public inputs are the commitment and a nonce chosen by the verifier. The
+/// nonce takes no part in any constraint of its own, but bellman ties every public
+/// input to the proof, so a proof for one nonce does not verify for another.
+#[derive(Clone, Debug)]
+pub struct PreimageCircuit {
+    preimage: Option<Scalar>,
+    nonce: Option<Scalar>,
+}
+
+impl PreimageCircuit {
+    /// A circuit with the given witness and nonce.
+    pub fn new(preimage: Scalar, nonce: Scalar) -> Self {
+        Self {
+            preimage: Some(preimage),
+            nonce: Some(nonce),
+        }
+    }
+
+    /// A circuit without assignments, to generate parameters with.
+    pub fn blank() -> Self {
+        Self {
+            preimage: None,
+            nonce: None,
+        }
+    }
+}
+
+impl Circuit<Scalar> for PreimageCircuit {
+    fn synthesize<CS: ConstraintSystem<Scalar>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let mut xl_value = self.preimage;
+        let mut xl = cs.alloc(
+            || "preimage",
+            || xl_value.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        let mut xr_value = Some(Scalar::ZERO);
+        let mut xr = LinearCombination::<Scalar>::zero();
+
+        for (i, c) in MIMC_CONSTANTS.iter().enumerate() {
+            let cs = &mut cs.namespace(|| format!("round {}", i));
+
+            // t = (x_L + C_i)^2
+            let t_value = xl_value.map(|x| (x + c).square());
+            let t = cs.alloc(|| "t", || t_value.ok_or(SynthesisError::AssignmentMissing))?;
+            cs.enforce(
+                || "t = (x_L + C_i)^2",
+                |lc| lc + xl + (*c, CS::one()),
+                |lc| lc + xl + (*c, CS::one()),
+                |lc| lc + t,
+            );
+
+            // x_L' = x_R + (x_L + C_i)^3, the commitment after the last round.
+            let next_value = xl_value
+                .zip(t_value)
+                .zip(xr_value)
+                .map(|((x, t), r)| r + t * (x + c));
+            let next = if i == MIMC_ROUNDS - 1 {
+                cs.alloc_input(
+                    || "commitment",
+                    || next_value.ok_or(SynthesisError::AssignmentMissing),
+                )?
+            } else {
+                cs.alloc(
+                    || "x_L",
+                    || next_value.ok_or(SynthesisError::AssignmentMissing),
+                )?
+            };
+            cs.enforce(
+                || "x_L' = x_R + (x_L + C_i)^3",
+                |lc| lc + t,
+                |lc| lc + xl + (*c, CS::one()),
+                |lc| lc + next - &xr,
+            );
+
+            xr = LinearCombination::zero() + xl;
+            xr_value = xl_value;
+            xl = next;
+            xl_value = next_value;
+        }
+
+        cs.alloc_input(
+            || "nonce",
+            || self.nonce.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        Ok(())
+    }
+}
+
+/// The Groth16 parameters of [`PreimageCircuit`] with the prepared verifying key.
+///
+/// They come from a trusted setup: whoever knows the randomness of [`setup`] can
+/// forge proofs, so it must be run by the verifier or in a ceremony and discarded.
+pub struct PreimageParams {
+    params: Parameters<Bls12>,
+    pvk: PreparedVerifyingKey<Bls12>,
+}
+
+impl PreimageParams {
+    /// Wraps parameters generated elsewhere.
+    ///
+    /// # Returns
+    /// The parameters, or [`Error::InvalidGroupParams`] if they are not for a
+    /// circuit with the public inputs of [`PreimageCircuit`].
+    pub fn new(params: Parameters<Bls12>) -> Result<Self, Error> {
+        if params.vk.ic.len() != PUBLIC_INPUTS + 1 {
+            return Err(Error::InvalidGroupParams(format!(
+                "verifying key for {} public inputs",
+                params.vk.ic.len().saturating_sub(1)
+            )));
+        }
+        let pvk = groth16::prepare_verifying_key(&params.vk);
+        Ok(Self { params, pvk })
+    }
+
+    /// The proving and verifying keys.
+    pub fn parameters(&self) -> &Parameters<Bls12> {
+        &self.params
+    }
+}
+
+/// Runs the trusted setup of [`PreimageCircuit`].
+pub fn setup<R: RngCore + CryptoRng>(rng: &mut R) -> PreimageParams {
+    // The blank circuit is well-formed, so synthesis cannot fail.
+    let params = groth16::generate_random_parameters::<Bls12, _, _>(PreimageCircuit::blank(), rng)
+        .expect("the preimage circuit is well-formed");
+    PreimageParams::new(params).expect("the preimage circuit has two public inputs")
+}
+
+/// Proves knowledge of the preimage `x` of `mimc(x)`, bound to `nonce`.
+///
+/// # Returns
+/// The proof, or [`Error::InvalidWitness`] if the circuit cannot be synthesized.
+pub fn prove<R: RngCore + CryptoRng>(
+    params: &PreimageParams,
+    x: &SecretScalar<Scalar>,
+    nonce: &Scalar,
+    rng: &mut R,
+) -> Result<Proof<Bls12>, Error> {
+    let circuit = PreimageCircuit::new(*x.expose_secret(), *nonce);
+    groth16::create_random_proof(circuit, &params.params, rng).map_err(|_| Error::InvalidWitness)
+}
+
+/// Verifies a proof of knowledge of a preimage of `commitment`, bound to `nonce`.
+///
+/// # Returns
+/// Whether the proof is valid, or [`Error::InvalidGroupParams`] if the verifying
+/// key does not match the circuit.
+pub fn verify(
+    params: &PreimageParams,
+    proof: &Proof<Bls12>,
+    commitment: &Scalar,
+    nonce: &Scalar,
+) -> Result<bool, Error> {
+    match groth16::verify_proof(&params.pvk, proof, &[*commitment, *nonce]) {
+        Ok(()) => Ok(true),
+        Err(VerificationError::InvalidProof) => Ok(false),
+        Err(VerificationError::InvalidVerifyingKey) => Err(Error::InvalidGroupParams(
+            "verifying key does not match the circuit".to_string(),
+        )),
+    }
+}
+
+/// The Groth16 login scheme: the user registers `mimc(x)` and answers a nonce with
+/// a succinct proof of knowledge of `x`.
+///
+/// Unlike [`DleqEllipticCurve`](super::elliptic_curves::dleq::DleqEllipticCurve),
+/// the commitment is the same for every run and the commitment randomness is the
+/// blinding `(r, s)` of the proof.
+pub struct Groth16Preimage;
+
+impl Protocol for Groth16Preimage {
+    type Secret = SecretScalar<Scalar>;
+    type Response = Proof<Bls12>;
+    type Challenge = Scalar;
+    type GroupParameters = PreimageParams;
+    type CommitParameters = Scalar;
+    type CommitmentRandom = (Nonce<Scalar>, Nonce<Scalar>);
+
+    /// Computes the commitment `mimc(x)` and draws the proof blinding from `rng`.
+    fn commitment_with_rng<R: RngCore + CryptoRng>(
+        _: &Self::GroupParameters,
+        x: &Self::Secret,
+        rng: &mut R,
+    ) -> Result<(Self::CommitParameters, Self::CommitmentRandom), Error> {
+        Ok((
+            mimc(x.expose_secret()),
+            (Nonce::random(rng), Nonce::random(rng)),
+        ))
+    }
+
+    /// Draws a random nonce.
+    fn challenge_with_rng<R: RngCore + CryptoRng>(
+        _: &Self::GroupParameters,
+        rng: &mut R,
+    ) -> Self::Challenge {
+        Scalar::random(rng)
+    }
+
+    /// Proves knowledge of `x` for the nonce `c`, blinded with `k`.
+    ///
+    /// # Panics
+    /// If the circuit cannot be synthesized, which does not happen for a complete witness.
+    fn challenge_response(
+        params: &Self::GroupParameters,
+        k: Self::CommitmentRandom,
+        c: &Self::Challenge,
+        x: &Self::Secret,
+    ) -> Self::Response {
+        let (r, s) = k;
+        let circuit = PreimageCircuit::new(*x.expose_secret(), *c);
+        groth16::create_proof(circuit, &params.params, r.consume(), s.consume())
+            .expect("the witness is complete")
+    }
+
+    /// Verifies the proof against the commitment and the nonce, see [`verify`].
+    fn verify(
+        params: &Self::GroupParameters,
+        s: &Self::Response,
+        c: &Self::Challenge,
+        cp: &Self::CommitParameters,
+    ) -> Result<bool, Error> {
+        verify(params, s, cp, c)
+    }
+}
+
+impl IntoBytes<PreimageParams> for PreimageParams {
+    /// Encodes the parameters in bellman's uncompressed format.
+    fn to(t: &PreimageParams) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        t.params
+            .write(&mut bytes)
+            .expect("writing to a vector does not fail");
+        bytes
+    }
+}
+
+impl FromBytes<PreimageParams> for PreimageParams {
+    /// Decodes parameters, checking that every point is in its subgroup.
+    fn from(bytes: &[u8]) -> Result<PreimageParams, Error> {
+        let mut reader = bytes;
+        let params = Parameters::read(&mut reader, true)
+            .map_err(|e| Error::InvalidGroupParams(e.to_string()))?;
+        if !reader.is_empty() {
+            return Err(Error::NonCanonicalEncoding);
+        }
+        PreimageParams::new(params)
+    }
+}
+
+impl IntoBytes<Proof<Bls12>> for Proof<Bls12> {
+    fn to(t: &Proof<Bls12>) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(PROOF_LEN);
+        t.write(&mut bytes)
+            .expect("writing to a vector does not fail");
+        bytes
+    }
+}
+
+impl FromBytes<Proof<Bls12>> for Proof<Bls12> {
+    fn from(bytes: &[u8]) -> Result<Proof<Bls12>, Error> {
+        if bytes.len() != PROOF_LEN {
+            return Err(Error::InvalidLength {
+                expected: PROOF_LEN,
+                actual: bytes.len(),
+            });
+        }
+        Proof::read(bytes).map_err(|_| Error::OffCurvePoint)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lazy_static::lazy_static;
+    use rand_core::OsRng;
+
+    lazy_static! {
+        /// The setup is the slowest step, so the tests share one.
+        static ref PARAMS: PreimageParams = setup(&mut OsRng);
+    }
+
+    #[test]
+    fn preimage_proof() {
+        let params = &*PARAMS;
+        let x = SecretScalar::random(&mut OsRng);
+        let commitment = mimc(x.expose_secret());
+        let nonce = Scalar::random(&mut OsRng);
+
+        let proof = prove(params, &x, &nonce, &mut OsRng).unwrap();
+        assert_eq!(verify(params, &proof, &commitment, &nonce), Ok(true));
+        let other_nonce = Scalar::random(&mut OsRng);
+        assert_eq!(verify(params, &proof, &commitment, &other_nonce), Ok(false));
+        let other_commitment = mimc(&Scalar::random(&mut OsRng));
+        assert_eq!(verify(params, &proof, &other_commitment, &nonce), Ok(false));
+
+        // A proof for a wrong preimage does not verify against the commitment.
+        let wrong = SecretScalar::new(*x.expose_secret() + Scalar::ONE);
+        let proof = prove(params, &wrong, &nonce, &mut OsRng).unwrap();
+        assert_eq!(verify(params, &proof, &commitment, &nonce), Ok(false));
+    }
+
+    #[test]
+    fn groth16_protocol() {
+        let params = &*PARAMS;
+        let x = SecretScalar::random(&mut OsRng);
+        let (cp, k) = Groth16Preimage::commitment(params, &x).unwrap();
+        let c = Groth16Preimage::challenge(params);
+        let s = Groth16Preimage::challenge_response(params, k, &c, &x);
+        assert_eq!(Groth16Preimage::verify(params, &s, &c, &cp), Ok(true));
+        assert_eq!(
+            Groth16Preimage::verify(params, &s, &(c + Scalar::ONE), &cp),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn groth16_round_trips_through_bytes() {
+        let params = &*PARAMS;
+        let bytes = PreimageParams::to(params);
+        let decoded = <PreimageParams as FromBytes<_>>::from(&bytes).unwrap();
+        assert!(decoded.parameters().vk == params.parameters().vk);
+        assert!(matches!(
+            <PreimageParams as FromBytes<_>>::from(&bytes[..bytes.len() - 1]),
+            Err(Error::InvalidGroupParams(_))
+        ));
+        assert_eq!(
+            <PreimageParams as FromBytes<_>>::from(&[bytes.as_slice(), &[0]].concat()).err(),
+            Some(Error::NonCanonicalEncoding)
+        );
+
+        let x = SecretScalar::random(&mut OsRng);
+        let nonce = Scalar::random(&mut OsRng);
+        let proof = prove(&decoded, &x, &nonce, &mut OsRng).unwrap();
+        let bytes = Proof::to(&proof);
+        assert_eq!(bytes.len(), PROOF_LEN);
+        let decoded_proof = <Proof<Bls12> as FromBytes<_>>::from(&bytes).unwrap();
+        assert!(decoded_proof == proof);
+        assert_eq!(
+            verify(params, &decoded_proof, &mimc(x.expose_secret()), &nonce),
+            Ok(true)
+        );
+        assert_eq!(
+            <Proof<Bls12> as FromBytes<_>>::from(&bytes[1..]).err(),
+            Some(Error::InvalidLength {
+                expected: PROOF_LEN,
+                actual: PROOF_LEN - 1
+            })
+        );
+        assert_eq!(
+            <Proof<Bls12> as FromBytes<_>>::from(&[0xff; PROOF_LEN]).err(),
+            Some(Error::OffCurvePoint)
+        );
+    }
+}
@@ -1,17 +1,42 @@
 pub mod constants;
+pub mod cross_group;
+pub mod elgamal;
 pub mod elliptic_curves;
+pub mod fiat_shamir;
+pub mod groth16;
+pub mod msm;
+pub mod pedersen;
+pub mod range_proof;
+pub mod rotation;
+pub mod sigma;
+pub mod threshold;
+pub mod transcript;
+pub mod voprf;
+pub mod vss;
+
+use crate::common::EllipticCurve;
+use crate::Error;
+use rand_core::{CryptoRng, OsRng, RngCore};
 
 /// A struct representing group parameters in cryptographic protocols.
+///
+/// Construct it with [`GroupParams::new`] or [`GroupParams::derive`], which fill in
+/// the curve metadata, and check parameters from untrusted sources with
+/// [`GroupParams::validate`].
 #[derive(Copy, Clone, Debug)]
 pub struct GroupParams<T> {
+    /// The curve the generators belong to.
+    pub curve: EllipticCurve,
     /// The generator `g` of the group.
     pub g: T,
     /// An additional generator `h` of the group, ensuring it's independent from `g`.
     pub h: T,
-    /// The prime modulus `p` defining the size of the group.
-    pub p: T,
-    /// The order `q` of the subgroup generated by `g` and `h`.
-    pub q: T,
+    /// The modulus of the base field of the curve, as a `0x`-prefixed hex string.
+    pub field_modulus: &'static str,
+    /// The prime order of the group generated by `g` and `h`, as a `0x`-prefixed hex string.
+    pub order: &'static str,
+    /// The cofactor of that group in the group of curve points, as a `0x`-prefixed hex string.
+    pub cofactor: &'static str,
 }
 
 /// A trait defining the interface for the zero-knowledge protocol.
@@ -26,37 +51,67 @@ pub trait Protocol {
     type CommitParameters;
     type CommitmentRandom;
 
-    /// Calculates the commitment in the protocol.
+    /// Calculates the commitment in the protocol, drawing the commitment randomness from `rng`.
     ///
     /// # Arguments
     /// * `params` - Group parameters used in the protocol.
     /// * `x` - The secret value for which the commitment is calculated.
+    /// * `rng` - The source of the commitment randomness.
     ///
     /// # Returns
-    /// A tuple containing the commitment parameters and the commitment randomness.
-    fn commitment(
+    /// A tuple containing the commitment parameters and the commitment randomness,
+    /// or an error if the secret yields a degenerate statement.
+    fn commitment_with_rng<R: RngCore + CryptoRng>(
         params: &Self::GroupParameters,
         x: &Self::Secret,
-    ) -> (Self::CommitParameters, Self::CommitmentRandom)
+        rng: &mut R,
+    ) -> Result<(Self::CommitParameters, Self::CommitmentRandom), Error>
     where
         Self: Sized;
 
-    /// Generates a challenge in the protocol.
+    /// Calculates the commitment in the protocol with randomness from `OsRng`.
+    ///
+    /// See [`Protocol::commitment_with_rng`].
+    fn commitment(
+        params: &Self::GroupParameters,
+        x: &Self::Secret,
+    ) -> Result<(Self::CommitParameters, Self::CommitmentRandom), Error>
+    where
+        Self: Sized,
+    {
+        Self::commitment_with_rng(params, x, &mut OsRng)
+    }
+
+    /// Generates a challenge in the protocol, drawing it from `rng`.
     ///
     /// # Arguments
     /// * `params` - Group parameters used in the protocol.
+    /// * `rng` - The source of the challenge.
     ///
     /// # Returns
     /// The challenge value used in the protocol.
-    fn challenge(params: &Self::GroupParameters) -> Self::Challenge
+    fn challenge_with_rng<R: RngCore + CryptoRng>(
+        params: &Self::GroupParameters,
+        rng: &mut R,
+    ) -> Self::Challenge
     where
         Self: Sized;
 
+    /// Generates a challenge in the protocol from `OsRng`.
+    ///
+    /// See [`Protocol::challenge_with_rng`].
+    fn challenge(params: &Self::GroupParameters) -> Self::Challenge
+    where
+        Self: Sized,
+    {
+        Self::challenge_with_rng(params, &mut OsRng)
+    }
+
     /// Calculates the challenge response in the protocol.
     ///
     /// # Arguments
     /// * `params` - Group parameters used in the protocol.
-    /// * `k` - The commitment randomness used in the protocol.
+    /// * `k` - The commitment randomness used in the protocol, consumed by the response.
     /// * `c` - The challenge value used in the protocol.
     /// * `x` - The secret value for which the response is calculated.
     ///
@@ -64,7 +119,7 @@ pub trait Protocol {
     /// The response value in the protocol.
     fn challenge_response(
         params: &Self::GroupParameters,
-        k: &Self::CommitmentRandom,
+        k: Self::CommitmentRandom,
         c: &Self::Challenge,
         x: &Self::Secret,
     ) -> Self::Response
@@ -80,13 +135,46 @@ pub trait Protocol {
     /// * `cp` - The commitment parameters used in the protocol.
     ///
     /// # Returns
-    /// A boolean indicating whether the verification was successful.
+    /// A boolean indicating whether the verification was successful,
+    /// or an error if the commitment parameters are malformed.
     fn verify(
         params: &Self::GroupParameters,
         s: &Self::Response,
         c: &Self::Challenge,
         cp: &Self::CommitParameters,
-    ) -> bool
+    ) -> Result<bool, Error>
     where
         Self: Sized;
+
+    /// Verifies many responses at once.
+    ///
+    /// The default implementation verifies each transcript on its own; implementations
+    /// can override it with a cheaper combined check.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters used in the protocol.
+    /// * `transcripts` - Tuples of the response, the challenge and the commitment parameters.
+    ///
+    /// # Returns
+    /// `Ok(())` if every transcript is valid, otherwise the indices of the invalid ones.
+    /// Transcripts for which [`Protocol::verify`] returns an error count as invalid.
+    fn verify_batch(
+        params: &Self::GroupParameters,
+        transcripts: &[(Self::Response, Self::Challenge, Self::CommitParameters)],
+    ) -> Result<(), Vec<usize>>
+    where
+        Self: Sized,
+    {
+        let invalid: Vec<usize> = transcripts
+            .iter()
+            .enumerate()
+            .filter(|(_, (s, c, cp))| Self::verify(params, s, c, cp) != Ok(true))
+            .map(|(i, _)| i)
+            .collect();
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid)
+        }
+    }
 }
@@ -0,0 +1,117 @@
+use pasta_curves::group::ff::{PrimeField, PrimeFieldBits};
+use pasta_curves::group::Group;
+
+/// Computes `sum(scalars[i] * points[i])` with Pippenger's bucket method.
+///
+/// Each scalar is split into windows of `c` bits. For every window the points are
+/// sorted into `2^c - 1` buckets by their window value, and the buckets are summed
+/// with a running sum, so a window costs one addition per point plus `2^(c+1)`.
+///
+/// The bucket a point goes to depends on the bits of its scalar, so the running time
+/// leaks the scalars. Only use it for public scalars, e.g. in verifiers, and
+/// [`linear_combination`] for secret ones.
+///
+/// # Panics
+///
+/// Panics if `scalars` and `points` have different lengths.
+pub fn multiscalar_mul<G>(scalars: &[G::Scalar], points: &[G]) -> G
+where
+    G: Group,
+    G::Scalar: PrimeFieldBits,
+{
+    assert_eq!(scalars.len(), points.len(), "Mismatched MSM input lengths");
+
+    let num_bits = G::Scalar::NUM_BITS as usize;
+    let c = window_size(points.len());
+    let windows = num_bits.div_ceil(c);
+    let bits: Vec<_> = scalars.iter().map(|s| s.to_le_bits()).collect();
+
+    let mut acc = G::identity();
+    for w in (0..windows).rev() {
+        for _ in 0..c {
+            acc = acc.double();
+        }
+
+        let mut buckets = vec![G::identity(); (1 << c) - 1];
+        for (scalar_bits, point) in bits.iter().zip(points) {
+            let mut index = 0usize;
+            for j in 0..c {
+                let bit = w * c + j;
+                if bit < num_bits && scalar_bits[bit] {
+                    index |= 1 << j;
+                }
+            }
+            if index != 0 {
+                buckets[index - 1] += point;
+            }
+        }
+
+        // sum_{k} k * bucket[k] computed as a sum of suffix sums
+        let mut running = G::identity();
+        let mut window_sum = G::identity();
+        for bucket in buckets.iter().rev() {
+            running += bucket;
+            window_sum += running;
+        }
+        acc += window_sum;
+    }
+    acc
+}
+
+/// Computes `sum(scalars[i] * points[i])` with one scalar multiplication per term.
+///
+/// The scalar multiplications of all backends run in constant time, so unlike
+/// [`multiscalar_mul`] this is safe for secret scalars such as witnesses and nonces.
+///
+/// # Panics
+///
+/// Panics if `scalars` and `points` have different lengths.
+pub fn linear_combination<G: Group>(scalars: &[G::Scalar], points: &[G]) -> G {
+    assert_eq!(scalars.len(), points.len(), "Mismatched input lengths");
+    scalars
+        .iter()
+        .zip(points)
+        .fold(G::identity(), |acc, (s, p)| acc + *p * s)
+}
+
+/// Picks the bucket window size for an MSM of `n` points.
+fn window_size(n: usize) -> usize {
+    if n < 32 {
+        3
+    } else {
+        // roughly ln(n) + 2, which balances bucket additions against bucket sums
+        (usize::BITS - n.leading_zeros()) as usize * 69 / 100 + 2
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pasta_curves::group::ff::Field;
+    use pasta_curves::pallas::{Point, Scalar};
+    use rand_core::OsRng;
+
+    fn naive(scalars: &[Scalar], points: &[Point]) -> Point {
+        scalars.iter().zip(points).map(|(s, p)| p * s).sum()
+    }
+
+    #[test]
+    fn msm_matches_naive_sum() {
+        for n in [0, 1, 2, 7, 33, 100] {
+            let scalars: Vec<Scalar> = (0..n).map(|_| Scalar::random(OsRng)).collect();
+            let points: Vec<Point> = (0..n).map(|_| Point::random(OsRng)).collect();
+            assert_eq!(multiscalar_mul(&scalars, &points), naive(&scalars, &points));
+        }
+    }
+
+    #[test]
+    fn msm_handles_edge_scalars() {
+        let scalars = [Scalar::ZERO, Scalar::ONE, -Scalar::ONE];
+        let points: Vec<Point> = (0..3).map(|_| Point::random(OsRng)).collect();
+        assert_eq!(multiscalar_mul(&scalars, &points), naive(&scalars, &points));
+        assert_eq!(
+            linear_combination(&scalars, &points),
+            naive(&scalars, &points)
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use crate::common::{FromBytes, IntoBytes};
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::sigma::linear::{Linear, LinearRelation};
+use crate::protocol::sigma::{self, SigmaProof};
+use crate::protocol::GroupParams;
+use crate::secret::SecretScalar;
+use crate::Error;
+use pasta_curves::group::ff::Field;
+use rand_core::{CryptoRng, RngCore};
+use std::ops::{Add, Mul, Sub};
+
+/// Domain-separation context of opening proofs, see [`Commitment::prove_opening`].
+pub const OPENING_CONTEXT: &[u8] = b"ec_snark.pedersen.opening.v1";
+
+/// A Pedersen commitment `C = g^v * h^r` to a value `v` with blinding `r`.
+///
+/// The commitment hides `v` perfectly and binds the committer to it as long as
+/// `log_g(h)` is unknown, which holds for the generators of [`GroupParams::derive`].
+/// Commitments are additively homomorphic: the sum of two commitments is a
+/// commitment to the sum of the values under the sum of the blindings.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Commitment<G> {
+    point: G,
+}
+
+/// The secrets `(v, r)` that open a [`Commitment`].
+///
+/// Openings follow the same arithmetic as commitments, so the opening of a sum of
+/// commitments is the sum of their openings.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Opening<F: Field> {
+    /// The committed value.
+    pub value: SecretScalar<F>,
+    /// The blinding factor.
+    pub blinding: SecretScalar<F>,
+}
+
+/// A zero-knowledge proof of knowledge of the opening of a [`Commitment`].
+pub type OpeningProof<G> = SigmaProof<Linear<G>>;
+
+impl<F: Field> Opening<F> {
+    /// Creates the opening of `value` with a random blinding drawn from `rng`.
+    pub fn random<R: RngCore + CryptoRng>(value: F, rng: &mut R) -> Self {
+        Self {
+            value: SecretScalar::new(value),
+            blinding: SecretScalar::random(rng),
+        }
+    }
+}
+
+impl<G: CurveBackend> Commitment<G> {
+    /// Commits to `value` with the given blinding.
+    pub fn commit(
+        params: &GroupParams<G>,
+        value: &G::Scalar,
+        blinding: &SecretScalar<G::Scalar>,
+    ) -> Self {
+        Self {
+            point: params.g * value + params.h * blinding.expose_secret(),
+        }
+    }
+
+    /// Commits to `value` with a random blinding drawn from `rng`.
+    ///
+    /// # Returns
+    /// The commitment and its opening, which the committer keeps secret.
+    pub fn commit_with_rng<R: RngCore + CryptoRng>(
+        params: &GroupParams<G>,
+        value: G::Scalar,
+        rng: &mut R,
+    ) -> (Self, Opening<G::Scalar>) {
+        let opening = Opening::random(value, rng);
+        (Self::open_to(params, &opening), opening)
+    }
+
+    /// Wraps a group element received from elsewhere, e.g. from storage.
+    pub fn from_point(point: G) -> Self {
+        Self { point }
+    }
+
+    /// The committed group element.
+    pub fn point(&self) -> G {
+        self.point
+    }
+
+    /// Checks whether `opening` opens this commitment.
+    pub fn verify_opening(&self, params: &GroupParams<G>, opening: &Opening<G::Scalar>) -> bool {
+        Self::open_to(params, opening) == *self
+    }
+
+    /// The statement `PK{(v, r): C = v·g + r·h}` proven by [`Commitment::prove_opening`].
+    pub fn relation(&self, params: &GroupParams<G>) -> LinearRelation<G> {
+        let (g, h, c) = (params.g, params.h, self.point);
+        crate::linear_relation! {
+            scalars: [v, r],
+            c = v * g + r * h,
+        }
+    }
+
+    /// Proves knowledge of the opening without revealing the value or the blinding.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters of the curve.
+    /// * `context` - Application data the proof is bound to, e.g. an account identifier.
+    /// * `opening` - The opening of this commitment.
+    /// * `rng` - The source of the prover's randomness.
+    ///
+    /// # Returns
+    /// The proof, or [`Error::InvalidWitness`] if `opening` does not open this commitment.
+    pub fn prove_opening<R: RngCore + CryptoRng>(
+        &self,
+        params: &GroupParams<G>,
+        context: &[u8],
+        opening: &Opening<G::Scalar>,
+        rng: &mut R,
+    ) -> Result<OpeningProof<G>, Error> {
+        let witness = vec![
+            SecretScalar::new(*opening.value.expose_secret()),
+            SecretScalar::new(*opening.blinding.expose_secret()),
+        ];
+        sigma::prove::<Linear<G>, R>(
+            &[OPENING_CONTEXT, context].concat(),
+            &self.relation(params),
+            &witness,
+            rng,
+        )
+    }
+
+    /// Verifies a proof produced by [`Commitment::prove_opening`] with the same context.
+    pub fn verify_opening_proof(
+        &self,
+        params: &GroupParams<G>,
+        context: &[u8],
+        proof: &OpeningProof<G>,
+    ) -> Result<bool, Error> {
+        sigma::verify(
+            &[OPENING_CONTEXT, context].concat(),
+            &self.relation(params),
+            proof,
+        )
+    }
+
+    fn open_to(params: &GroupParams<G>, opening: &Opening<G::Scalar>) -> Self {
+        Self::commit(params, opening.value.expose_secret(), &opening.blinding)
+    }
+}
+
+impl<G: CurveBackend> Add for Commitment<G> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            point: self.point + rhs.point,
+        }
+    }
+}
+
+impl<G: CurveBackend> Sub for Commitment<G> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            point: self.point - rhs.point,
+        }
+    }
+}
+
+impl<G: CurveBackend> Mul<G::Scalar> for Commitment<G> {
+    type Output = Self;
+
+    fn mul(self, rhs: G::Scalar) -> Self {
+        Self {
+            point: self.point * rhs,
+        }
+    }
+}
+
+impl<F: Field> Add for &Opening<F> {
+    type Output = Opening<F>;
+
+    fn add(self, rhs: Self) -> Opening<F> {
+        Opening {
+            value: SecretScalar::new(*self.value.expose_secret() + rhs.value.expose_secret()),
+            blinding: SecretScalar::new(
+                *self.blinding.expose_secret() + rhs.blinding.expose_secret(),
+            ),
+        }
+    }
+}
+
+impl<F: Field> Sub for &Opening<F> {
+    type Output = Opening<F>;
+
+    fn sub(self, rhs: Self) -> Opening<F> {
+        Opening {
+            value: SecretScalar::new(*self.value.expose_secret() - rhs.value.expose_secret()),
+            blinding: SecretScalar::new(
+                *self.blinding.expose_secret() - rhs.blinding.expose_secret(),
+            ),
+        }
+    }
+}
+
+impl<F: Field> Mul<F> for &Opening<F> {
+    type Output = Opening<F>;
+
+    fn mul(self, rhs: F) -> Opening<F> {
+        Opening {
+            value: SecretScalar::new(*self.value.expose_secret() * rhs),
+            blinding: SecretScalar::new(*self.blinding.expose_secret() * rhs),
+        }
+    }
+}
+
+impl<G: CurveBackend> IntoBytes<Commitment<G>> for Commitment<G> {
+    fn to(t: &Commitment<G>) -> Vec<u8> {
+        G::to(&t.point)
+    }
+}
+
+impl<G: CurveBackend + FromBytes<G>> FromBytes<Commitment<G>> for Commitment<G> {
+    /// Decodes a commitment with the strict point decoding of the curve.
+    fn from(bytes: &[u8]) -> Result<Commitment<G>, Error> {
+        Ok(Self::from_point(<G as FromBytes<G>>::from(bytes)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::constants::{PALLAS_GROUP_PARAMS, VESTA_GROUP_PARAMS};
+    use pasta_curves::{pallas, vesta};
+    use rand_core::OsRng;
+
+    fn homomorphism<G: CurveBackend>(params: &GroupParams<G>) {
+        let (a, opening_a) = Commitment::commit_with_rng(params, G::Scalar::from(70), &mut OsRng);
+        let (b, opening_b) = Commitment::commit_with_rng(params, G::Scalar::from(30), &mut OsRng);
+        assert!(a.verify_opening(params, &opening_a));
+        assert!(!a.verify_opening(params, &opening_b));
+
+        let sum = &opening_a + &opening_b;
+        assert_eq!(*sum.value.expose_secret(), G::Scalar::from(100));
+        assert!((a + b).verify_opening(params, &sum));
+        assert!((a - b).verify_opening(params, &(&opening_a - &opening_b)));
+        let three = G::Scalar::from(3);
+        assert!((a * three).verify_opening(params, &(&opening_a * three)));
+    }
+
+    fn opening_proof<G: CurveBackend>(params: &GroupParams<G>) {
+        let (c, opening) = Commitment::commit_with_rng(params, G::Scalar::from(42), &mut OsRng);
+        let proof = c
+            .prove_opening(params, b"account", &opening, &mut OsRng)
+            .unwrap();
+        assert_eq!(c.verify_opening_proof(params, b"account", &proof), Ok(true));
+        assert_eq!(c.verify_opening_proof(params, b"other", &proof), Ok(false));
+
+        let (other, other_opening) =
+            Commitment::commit_with_rng(params, G::Scalar::from(42), &mut OsRng);
+        assert_eq!(
+            other.verify_opening_proof(params, b"account", &proof),
+            Ok(false)
+        );
+        assert_eq!(
+            c.prove_opening(params, b"account", &other_opening, &mut OsRng)
+                .unwrap_err(),
+            Error::InvalidWitness
+        );
+    }
+
+    #[test]
+    fn pallas_commitments() {
+        homomorphism::<pallas::Point>(&PALLAS_GROUP_PARAMS);
+        opening_proof::<pallas::Point>(&PALLAS_GROUP_PARAMS);
+    }
+
+    #[test]
+    fn vesta_commitments() {
+        homomorphism::<vesta::Point>(&VESTA_GROUP_PARAMS);
+        opening_proof::<vesta::Point>(&VESTA_GROUP_PARAMS);
+    }
+
+    #[test]
+    fn commitment_round_trips_through_bytes() {
+        let params = &*PALLAS_GROUP_PARAMS;
+        let (c, _) = Commitment::commit_with_rng(params, 7.into(), &mut OsRng);
+        let bytes = Commitment::to(&c);
+        assert_eq!(
+            <Commitment<pallas::Point> as FromBytes<_>>::from(&bytes),
+            Ok(c)
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::msm::{linear_combination, multiscalar_mul};
+use crate::protocol::transcript::Transcript;
+use crate::Error;
+use pasta_curves::group::ff::Field;
+
+/// A logarithmic-size proof that `P = <a, G> + <b, H> + <a, b> * Q`.
+///
+/// Each round halves the vectors and sends the cross terms `L` and `R`, so a
+/// proof for vectors of length `2^k` holds `k` pairs of points and two scalars.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InnerProductProof<G: CurveBackend> {
+    /// The left cross terms, one per round.
+    pub l_vec: Vec<G>,
+    /// The right cross terms, one per round.
+    pub r_vec: Vec<G>,
+    /// The folded `a` vector.
+    pub a: G::Scalar,
+    /// The folded `b` vector.
+    pub b: G::Scalar,
+}
+
+/// The challenges of an inner-product proof, replayed from the transcript.
+pub(crate) struct VerificationScalars<F> {
+    /// `u_j^2` for every round `j`.
+    pub u_sq: Vec<F>,
+    /// `u_j^-2` for every round `j`.
+    pub u_inv_sq: Vec<F>,
+    /// The coefficient `s_i` of `G_i` in the folded generator; `H_i` has `s_(n-1-i)`.
+    pub s: Vec<F>,
+}
+
+impl<G: CurveBackend> InnerProductProof<G> {
+    /// Proves the inner product of `a` and `b`.
+    ///
+    /// # Arguments
+    /// * `transcript` - The transcript the round challenges are drawn from.
+    /// * `q` - The generator the inner product is committed with.
+    /// * `g`, `h` - The generators of `a` and `b`.
+    /// * `a`, `b` - The vectors, of the same power-of-two length as the generators.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths differ or are not a power of two.
+    pub fn prove(
+        transcript: &mut Transcript,
+        q: &G,
+        g: &[G],
+        h: &[G],
+        a: &[G::Scalar],
+        b: &[G::Scalar],
+    ) -> Self {
+        let mut n = a.len();
+        assert!(n.is_power_of_two(), "Vector length is not a power of two");
+        assert!(
+            b.len() == n && g.len() == n && h.len() == n,
+            "Mismatched inner product input lengths"
+        );
+        let (mut g, mut h, mut a, mut b) = (g.to_vec(), h.to_vec(), a.to_vec(), b.to_vec());
+        let mut l_vec = Vec::with_capacity(n.trailing_zeros() as usize);
+        let mut r_vec = Vec::with_capacity(n.trailing_zeros() as usize);
+
+        transcript.append_message(b"ipp.n", &(n as u64).to_le_bytes());
+        while n > 1 {
+            n /= 2;
+            let (a_l, a_r) = a.split_at(n);
+            let (b_l, b_r) = b.split_at(n);
+            let (g_l, g_r) = g.split_at(n);
+            let (h_l, h_r) = h.split_at(n);
+
+            let c_l = inner_product(a_l, b_r);
+            let c_r = inner_product(a_r, b_l);
+            // The folded vectors are derived from the witness, so no variable-time MSM.
+            let l = linear_combination(&[a_l, b_r, &[c_l]].concat(), &[g_r, h_l, &[*q]].concat());
+            let r = linear_combination(&[a_r, b_l, &[c_r]].concat(), &[g_l, h_r, &[*q]].concat());
+            transcript.append_element(b"L", &l);
+            transcript.append_element(b"R", &r);
+            l_vec.push(l);
+            r_vec.push(r);
+
+            let u = transcript.challenge_scalar::<G>(b"u");
+            // A zero challenge only comes up with negligible probability.
+            let u_inv = u.invert().unwrap();
+            a = (0..n).map(|i| a_l[i] * u + a_r[i] * u_inv).collect();
+            b = (0..n).map(|i| b_l[i] * u_inv + b_r[i] * u).collect();
+            g = (0..n).map(|i| g_l[i] * u_inv + g_r[i] * u).collect();
+            h = (0..n).map(|i| h_l[i] * u + h_r[i] * u_inv).collect();
+        }
+
+        Self {
+            l_vec,
+            r_vec,
+            a: a[0],
+            b: b[0],
+        }
+    }
+
+    /// Verifies the proof for the commitment `p`.
+    ///
+    /// # Returns
+    /// Whether the proof is valid; a proof with the wrong number of rounds is invalid.
+    pub fn verify(
+        &self,
+        transcript: &mut Transcript,
+        q: &G,
+        g: &[G],
+        h: &[G],
+        p: &G,
+    ) -> Result<bool, Error> {
+        if g.len() != h.len() {
+            return Ok(false);
+        }
+        let Some(scalars) = self.verification_scalars(g.len(), transcript) else {
+            return Ok(false);
+        };
+        let n = g.len();
+        let g_scalars = scalars.s.iter().map(|s| self.a * s);
+        let h_scalars = scalars.s.iter().rev().map(|s| self.b * s);
+        let lr_scalars = scalars.u_sq.iter().chain(&scalars.u_inv_sq).map(|u| -*u);
+
+        let scalars: Vec<_> = g_scalars
+            .chain(h_scalars)
+            .chain(lr_scalars)
+            .chain([self.a * self.b, -G::Scalar::ONE])
+            .collect();
+        let mut points = Vec::with_capacity(2 * n + 2 * self.l_vec.len() + 2);
+        points.extend_from_slice(g);
+        points.extend_from_slice(h);
+        points.extend_from_slice(&self.l_vec);
+        points.extend_from_slice(&self.r_vec);
+        points.extend([*q, *p]);
+        Ok(bool::from(multiscalar_mul(&scalars, &points).is_identity()))
+    }
+
+    /// Replays the round challenges for vectors of length `n`.
+    ///
+    /// Returns `None` if the proof does not have `log2(n)` rounds or a challenge is zero.
+    pub(crate) fn verification_scalars(
+        &self,
+        n: usize,
+        transcript: &mut Transcript,
+    ) -> Option<VerificationScalars<G::Scalar>> {
+        let rounds = self.l_vec.len();
+        if !n.is_power_of_two() || n != 1 << rounds || self.r_vec.len() != rounds {
+            return None;
+        }
+
+        transcript.append_message(b"ipp.n", &(n as u64).to_le_bytes());
+        let mut challenges = Vec::with_capacity(rounds);
+        for (l, r) in self.l_vec.iter().zip(&self.r_vec) {
+            transcript.append_element(b"L", l);
+            transcript.append_element(b"R", r);
+            challenges.push(transcript.challenge_scalar::<G>(b"u"));
+        }
+        let inverses: Vec<G::Scalar> = challenges
+            .iter()
+            .map(|u| Option::from(u.invert()))
+            .collect::<Option<_>>()?;
+
+        // Round j folds on bit (rounds - 1 - j) of the index: the right half gets u_j.
+        let s = (0..n)
+            .map(|i| {
+                (0..rounds).fold(G::Scalar::ONE, |acc, j| {
+                    if (i >> (rounds - 1 - j)) & 1 == 1 {
+                        acc * challenges[j]
+                    } else {
+                        acc * inverses[j]
+                    }
+                })
+            })
+            .collect();
+
+        Some(VerificationScalars {
+            u_sq: challenges.iter().map(|u| u.square()).collect(),
+            u_inv_sq: inverses.iter().map(|u| u.square()).collect(),
+            s,
+        })
+    }
+}
+
+/// Computes `<a, b> = sum(a_i * b_i)`.
+pub(crate) fn inner_product<F: Field>(a: &[F], b: &[F]) -> F {
+    a.iter().zip(b).map(|(a, b)| *a * b).sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pasta_curves::pallas::{Point, Scalar};
+    use rand_core::OsRng;
+
+    #[test]
+    fn inner_product_proof_verifies() {
+        let n = 16;
+        let g: Vec<_> = (0..n)
+            .map(|i| Point::hash_to_group("test", &[b'G', i as u8]))
+            .collect();
+        let h: Vec<_> = (0..n)
+            .map(|i| Point::hash_to_group("test", &[b'H', i as u8]))
+            .collect();
+        let q = Point::hash_to_group("test", b"Q");
+        let a: Vec<_> = (0..n).map(|_| Scalar::random(OsRng)).collect();
+        let b: Vec<_> = (0..n).map(|_| Scalar::random(OsRng)).collect();
+        let p = multiscalar_mul(
+            &[&a[..], &b[..], &[inner_product(&a, &b)]].concat(),
+            &[&g[..], &h[..], &[q]].concat(),
+        );
+
+        let proof = InnerProductProof::prove(&mut Transcript::new(b"test"), &q, &g, &h, &a, &b);
+        assert_eq!(proof.l_vec.len(), 4);
+        let verify = |proof: &InnerProductProof<Point>, p: &Point| {
+            proof.verify(&mut Transcript::new(b"test"), &q, &g, &h, p)
+        };
+        assert_eq!(verify(&proof, &p), Ok(true));
+        assert_eq!(verify(&proof, &(p + q)), Ok(false));
+
+        let mut truncated = proof.clone();
+        truncated.l_vec.pop();
+        truncated.r_vec.pop();
+        assert_eq!(verify(&truncated, &p), Ok(false));
+    }
+}
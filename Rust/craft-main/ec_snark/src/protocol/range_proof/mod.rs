@@ -0,0 +1,502 @@
+pub mod inner_product;
+
+use crate::common::{self, FromBytes, IntoBytes};
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::msm::{linear_combination, multiscalar_mul};
+use crate::protocol::pedersen::{Commitment, Opening};
+use crate::protocol::transcript::Transcript;
+use crate::protocol::GroupParams;
+use crate::secret::Nonce;
+use crate::Error;
+use inner_product::{inner_product, InnerProductProof};
+use pasta_curves::group::ff::{Field, PrimeField, PrimeFieldBits};
+use rand_core::{CryptoRng, OsRng, RngCore};
+
+/// Domain-separation label of range proof transcripts.
+pub const RANGE_PROOF_DOMAIN: &[u8] = b"ec_snark.rangeproof.v1";
+
+/// Domain-separation prefix of the hash to the vector generators.
+pub const GENERATORS_DOMAIN: &str = "ec_snark.rangeproof.v1.generators";
+
+/// The supported bit sizes `n` of the range `[0, 2^n)`.
+pub const SUPPORTED_BITS: [usize; 4] = [8, 16, 32, 64];
+
+/// The vector generators `G_i` and `H_i` of the range proof.
+///
+/// They are hashed to the group from their index, so nobody knows discrete
+/// logarithms between them, `g` or `h`. A proof for `m` values of `n` bits needs
+/// a capacity of at least `n * m`.
+#[derive(Clone, Debug)]
+pub struct RangeProofGens<G> {
+    g_vec: Vec<G>,
+    h_vec: Vec<G>,
+}
+
+impl<G: CurveBackend> RangeProofGens<G> {
+    /// Derives `capacity` pairs of generators.
+    pub fn new(capacity: usize) -> Self {
+        let derive = |label: u8, i: usize| {
+            let mut message = vec![label];
+            message.extend_from_slice(&(i as u64).to_le_bytes());
+            G::hash_to_group(GENERATORS_DOMAIN, &message)
+        };
+        Self {
+            g_vec: (0..capacity).map(|i| derive(b'G', i)).collect(),
+            h_vec: (0..capacity).map(|i| derive(b'H', i)).collect(),
+        }
+    }
+
+    /// The number of generator pairs.
+    pub fn capacity(&self) -> usize {
+        self.g_vec.len()
+    }
+}
+
+/// An aggregated Bulletproofs range proof that `m` committed values lie in `[0, 2^n)`.
+///
+/// The values are committed with [`Commitment`], i.e. with `g` as the value and
+/// `h` as the blinding generator of the group parameters. The proof size grows
+/// with `log2(n * m)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeProof<G: CurveBackend> {
+    /// Commitment to the bits of the values.
+    pub a: G,
+    /// Commitment to the blinding vectors.
+    pub s: G,
+    /// Commitment to the linear coefficient of `t(X)`.
+    pub t1: G,
+    /// Commitment to the quadratic coefficient of `t(X)`.
+    pub t2: G,
+    /// The evaluation `t(x)`.
+    pub t_x: G::Scalar,
+    /// The blinding of `t(x)`.
+    pub t_x_blinding: G::Scalar,
+    /// The blinding of `A + x * S`.
+    pub e_blinding: G::Scalar,
+    /// The proof that `t(x) = <l(x), r(x)>`.
+    pub ipp: InnerProductProof<G>,
+}
+
+/// Checks the bit size and the number of values of a proof.
+fn check_range_params<G: CurveBackend>(
+    gens: &RangeProofGens<G>,
+    n: usize,
+    m: usize,
+) -> Result<(), Error> {
+    if !SUPPORTED_BITS.contains(&n) {
+        return Err(Error::InvalidRangeParams(format!(
+            "unsupported bit size {}",
+            n
+        )));
+    }
+    if !m.is_power_of_two() {
+        return Err(Error::InvalidRangeParams(format!(
+            "number of values {} is not a power of two",
+            m
+        )));
+    }
+    if gens.capacity() < n * m {
+        return Err(Error::InvalidRangeParams(format!(
+            "{} generators cannot prove {} values of {} bits",
+            gens.capacity(),
+            m,
+            n
+        )));
+    }
+    Ok(())
+}
+
+/// Starts the transcript of a proof, bound to the context and the commitments.
+fn start_transcript<G: CurveBackend>(
+    context: &[u8],
+    n: usize,
+    commitments: &[Commitment<G>],
+) -> Transcript {
+    let mut transcript = Transcript::new(RANGE_PROOF_DOMAIN);
+    transcript.append_message(b"context", context);
+    transcript.append_message(b"curve", G::CURVE.to_string().as_bytes());
+    transcript.append_message(b"n", &(n as u64).to_le_bytes());
+    transcript.append_message(b"m", &(commitments.len() as u64).to_le_bytes());
+    for commitment in commitments {
+        transcript.append_element(b"V", &commitment.point());
+    }
+    transcript
+}
+
+/// Computes `[1, x, x^2, ..., x^(n-1)]`.
+fn powers<F: Field>(x: F, n: usize) -> Vec<F> {
+    std::iter::successors(Some(F::ONE), |p| Some(*p * x))
+        .take(n)
+        .collect()
+}
+
+impl<G: CurveBackend> RangeProof<G> {
+    /// Proves that the values of all openings lie in `[0, 2^n)`.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters of the curve, providing the commitment generators.
+    /// * `gens` - The vector generators, with a capacity of at least `n * openings.len()`.
+    /// * `context` - Application data the proof is bound to, e.g. an account identifier.
+    /// * `openings` - The openings of the commitments; their number must be a power of two.
+    /// * `n` - The bit size of the range, one of [`SUPPORTED_BITS`].
+    /// * `rng` - The source of the prover's randomness.
+    ///
+    /// # Returns
+    /// The proof and the commitments it is about, [`Error::InvalidRangeParams`] for
+    /// unsupported sizes, or [`Error::InvalidWitness`] if a value is out of range.
+    pub fn prove<R: RngCore + CryptoRng>(
+        params: &GroupParams<G>,
+        gens: &RangeProofGens<G>,
+        context: &[u8],
+        openings: &[Opening<G::Scalar>],
+        n: usize,
+        rng: &mut R,
+    ) -> Result<(Self, Vec<Commitment<G>>), Error> {
+        let m = openings.len();
+        check_range_params(gens, n, m)?;
+        let nm = n * m;
+        let (g_vec, h_vec) = (&gens.g_vec[..nm], &gens.h_vec[..nm]);
+
+        let mut a_l = Vec::with_capacity(nm);
+        for opening in openings {
+            let bits = opening.value.expose_secret().to_le_bits();
+            if bits.iter().skip(n).any(|bit| *bit) {
+                return Err(Error::InvalidWitness);
+            }
+            a_l.extend(bits.iter().take(n).map(|bit| G::Scalar::from(*bit as u64)));
+        }
+        let a_r: Vec<_> = a_l.iter().map(|bit| *bit - G::Scalar::ONE).collect();
+        let commitments: Vec<_> = openings
+            .iter()
+            .map(|opening| {
+                Commitment::commit(params, opening.value.expose_secret(), &opening.blinding)
+            })
+            .collect();
+        let mut transcript = start_transcript(context, n, &commitments);
+
+        let alpha = Nonce::<G::Scalar>::random(rng);
+        let rho = Nonce::<G::Scalar>::random(rng);
+        let s_l: Vec<_> = (0..nm).map(|_| G::Scalar::random(&mut *rng)).collect();
+        let s_r: Vec<_> = (0..nm).map(|_| G::Scalar::random(&mut *rng)).collect();
+        // The bits of the values and the blindings are secret, so the prover uses
+        // constant-time scalar multiplications and only the verifier an MSM.
+        let a = linear_combination(
+            &[&[*alpha.expose_secret()], &a_l[..], &a_r[..]].concat(),
+            &[&[params.h], g_vec, h_vec].concat(),
+        );
+        let s = linear_combination(
+            &[&[*rho.expose_secret()], &s_l[..], &s_r[..]].concat(),
+            &[&[params.h], g_vec, h_vec].concat(),
+        );
+        transcript.append_element(b"A", &a);
+        transcript.append_element(b"S", &s);
+        let y = transcript.challenge_scalar::<G>(b"y");
+        let z = transcript.challenge_scalar::<G>(b"z");
+
+        // l(X) = (a_L - z) + s_L X and r(X) = y^i (a_R + z + s_R X) + z^(2+j) 2^k,
+        // where i = j * n + k indexes bit k of value j.
+        let y_powers = powers(y, nm);
+        let two_powers = powers(G::Scalar::from(2), n);
+        let z_powers = powers(z, m + 2);
+        let l0: Vec<_> = a_l.iter().map(|a| *a - z).collect();
+        let r0: Vec<_> = (0..nm)
+            .map(|i| y_powers[i] * (a_r[i] + z) + z_powers[2 + i / n] * two_powers[i % n])
+            .collect();
+        let r1: Vec<_> = (0..nm).map(|i| y_powers[i] * s_r[i]).collect();
+        let t1 = inner_product(&l0, &r1) + inner_product(&s_l, &r0);
+        let t2 = inner_product(&s_l, &r1);
+
+        let tau1 = Nonce::<G::Scalar>::random(rng);
+        let tau2 = Nonce::<G::Scalar>::random(rng);
+        let t1_point = params.g * t1 + params.h * tau1.expose_secret();
+        let t2_point = params.g * t2 + params.h * tau2.expose_secret();
+        transcript.append_element(b"T1", &t1_point);
+        transcript.append_element(b"T2", &t2_point);
+        let x = transcript.challenge_scalar::<G>(b"x");
+
+        let l: Vec<_> = (0..nm).map(|i| l0[i] + s_l[i] * x).collect();
+        let r: Vec<_> = (0..nm).map(|i| r0[i] + r1[i] * x).collect();
+        let t_x = inner_product(&l, &r);
+        let gamma_sum: G::Scalar = openings
+            .iter()
+            .zip(&z_powers[2..])
+            .map(|(opening, z)| *z * opening.blinding.expose_secret())
+            .sum();
+        let t_x_blinding = tau2.consume() * x.square() + tau1.consume() * x + gamma_sum;
+        let e_blinding = alpha.consume() + rho.consume() * x;
+        transcript.append_message(b"t_x", t_x.to_repr().as_ref());
+        transcript.append_message(b"t_x_blinding", t_x_blinding.to_repr().as_ref());
+        transcript.append_message(b"e_blinding", e_blinding.to_repr().as_ref());
+
+        let w = transcript.challenge_scalar::<G>(b"w");
+        let q = params.g * w;
+        // A zero challenge only comes up with negligible probability.
+        let y_inv = y.invert().unwrap();
+        let h_prime: Vec<_> = h_vec
+            .iter()
+            .zip(powers(y_inv, nm))
+            .map(|(h, y_inv)| *h * y_inv)
+            .collect();
+        let ipp = InnerProductProof::prove(&mut transcript, &q, g_vec, &h_prime, &l, &r);
+
+        let proof = Self {
+            a,
+            s,
+            t1: t1_point,
+            t2: t2_point,
+            t_x,
+            t_x_blinding,
+            e_blinding,
+            ipp,
+        };
+        Ok((proof, commitments))
+    }
+
+    /// Verifies the proof for the given commitments with a single multiscalar multiplication.
+    ///
+    /// The inner-product check and the check of `t(x)` against the commitments are
+    /// combined with a random weight, so both hold except with negligible probability
+    /// if the combination does.
+    ///
+    /// # Returns
+    /// Whether the proof is valid, or [`Error::InvalidRangeParams`] for unsupported sizes.
+    pub fn verify(
+        &self,
+        params: &GroupParams<G>,
+        gens: &RangeProofGens<G>,
+        context: &[u8],
+        commitments: &[Commitment<G>],
+        n: usize,
+    ) -> Result<bool, Error> {
+        let m = commitments.len();
+        check_range_params(gens, n, m)?;
+        let nm = n * m;
+
+        let mut transcript = start_transcript(context, n, commitments);
+        transcript.append_element(b"A", &self.a);
+        transcript.append_element(b"S", &self.s);
+        let y = transcript.challenge_scalar::<G>(b"y");
+        let z = transcript.challenge_scalar::<G>(b"z");
+        transcript.append_element(b"T1", &self.t1);
+        transcript.append_element(b"T2", &self.t2);
+        let x = transcript.challenge_scalar::<G>(b"x");
+        transcript.append_message(b"t_x", self.t_x.to_repr().as_ref());
+        transcript.append_message(b"t_x_blinding", self.t_x_blinding.to_repr().as_ref());
+        transcript.append_message(b"e_blinding", self.e_blinding.to_repr().as_ref());
+        let w = transcript.challenge_scalar::<G>(b"w");
+
+        let Some(ipp) = self.ipp.verification_scalars(nm, &mut transcript) else {
+            return Ok(false);
+        };
+        let Some(y_inv) = Option::<G::Scalar>::from(y.invert()) else {
+            return Ok(false);
+        };
+        let c = G::Scalar::random(OsRng);
+        let (a, b) = (self.ipp.a, self.ipp.b);
+
+        let y_powers = powers(y, nm);
+        let y_inv_powers = powers(y_inv, nm);
+        let two_powers = powers(G::Scalar::from(2), n);
+        let z_powers = powers(z, m + 3);
+        let sum_y: G::Scalar = y_powers.iter().sum();
+        let sum_two: G::Scalar = two_powers.iter().sum();
+        let delta = (z - z.square()) * sum_y - z_powers[3..].iter().sum::<G::Scalar>() * sum_two;
+
+        let g_scalars = ipp.s.iter().map(|s| -z - a * s);
+        let h_scalars = (0..nm).map(|i| {
+            z + y_inv_powers[i] * (z_powers[2 + i / n] * two_powers[i % n] - b * ipp.s[nm - 1 - i])
+        });
+        let v_scalars = z_powers[2..m + 2].iter().map(|z| c * z);
+        let scalars: Vec<_> = [G::Scalar::ONE, x, c * x, c * x.square()]
+            .into_iter()
+            .chain([
+                -self.e_blinding - c * self.t_x_blinding,
+                w * (self.t_x - a * b) + c * (delta - self.t_x),
+            ])
+            .chain(g_scalars)
+            .chain(h_scalars)
+            .chain(ipp.u_sq)
+            .chain(ipp.u_inv_sq)
+            .chain(v_scalars)
+            .collect();
+
+        let mut points = Vec::with_capacity(scalars.len());
+        points.extend([self.a, self.s, self.t1, self.t2, params.h, params.g]);
+        points.extend_from_slice(&gens.g_vec[..nm]);
+        points.extend_from_slice(&gens.h_vec[..nm]);
+        points.extend_from_slice(&self.ipp.l_vec);
+        points.extend_from_slice(&self.ipp.r_vec);
+        points.extend(commitments.iter().map(|commitment| commitment.point()));
+        Ok(bool::from(multiscalar_mul(&scalars, &points).is_identity()))
+    }
+}
+
+impl<G> IntoBytes<RangeProof<G>> for RangeProof<G>
+where
+    G: CurveBackend,
+    G::Scalar: IntoBytes<G::Scalar>,
+{
+    /// Encodes the number of inner-product rounds, the fixed fields, and the
+    /// `L` and `R` points of every round.
+    fn to(t: &RangeProof<G>) -> Vec<u8> {
+        let mut parts = vec![
+            vec![t.ipp.l_vec.len() as u8],
+            G::to(&t.a),
+            G::to(&t.s),
+            G::to(&t.t1),
+            G::to(&t.t2),
+            G::Scalar::to(&t.t_x),
+            G::Scalar::to(&t.t_x_blinding),
+            G::Scalar::to(&t.e_blinding),
+            G::Scalar::to(&t.ipp.a),
+            G::Scalar::to(&t.ipp.b),
+        ];
+        for (l, r) in t.ipp.l_vec.iter().zip(&t.ipp.r_vec) {
+            parts.push(G::to(l));
+            parts.push(G::to(r));
+        }
+        common::encode_parts(&parts)
+    }
+}
+
+impl<G> FromBytes<RangeProof<G>> for RangeProof<G>
+where
+    G: CurveBackend + FromBytes<G>,
+    G::Scalar: FromBytes<G::Scalar>,
+{
+    fn from(bytes: &[u8]) -> Result<RangeProof<G>, Error> {
+        let rounds = match bytes {
+            [0, 1, rounds, ..] => *rounds as usize,
+            _ => return Err(Error::NonCanonicalEncoding),
+        };
+        // Proofs for at most 64 values of 64 bits have 12 rounds.
+        if rounds > 12 {
+            return Err(Error::NonCanonicalEncoding);
+        }
+        let parts = common::decode_parts(bytes, 10 + 2 * rounds)?;
+        let point = |i: usize| <G as FromBytes<G>>::from(parts[i]);
+        let scalar = |i: usize| <G::Scalar as FromBytes<G::Scalar>>::from(parts[i]);
+        Ok(RangeProof {
+            a: point(1)?,
+            s: point(2)?,
+            t1: point(3)?,
+            t2: point(4)?,
+            t_x: scalar(5)?,
+            t_x_blinding: scalar(6)?,
+            e_blinding: scalar(7)?,
+            ipp: InnerProductProof {
+                l_vec: (0..rounds)
+                    .map(|j| point(10 + 2 * j))
+                    .collect::<Result<_, _>>()?,
+                r_vec: (0..rounds)
+                    .map(|j| point(11 + 2 * j))
+                    .collect::<Result<_, _>>()?,
+                a: scalar(8)?,
+                b: scalar(9)?,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::constants::{PALLAS_GROUP_PARAMS, VESTA_GROUP_PARAMS};
+    use pasta_curves::{pallas, vesta};
+
+    fn openings<F: PrimeField>(values: &[u64]) -> Vec<Opening<F>> {
+        values
+            .iter()
+            .map(|v| Opening::random((*v).into(), &mut OsRng))
+            .collect()
+    }
+
+    fn range_proofs<G>(params: &GroupParams<G>)
+    where
+        G: CurveBackend + FromBytes<G>,
+        G::Scalar: IntoBytes<G::Scalar> + FromBytes<G::Scalar>,
+    {
+        let gens = RangeProofGens::<G>::new(64 * 4);
+        let single = openings(&[u64::MAX]);
+        let (proof, commitments) =
+            RangeProof::prove(params, &gens, b"balance", &single, 64, &mut OsRng).unwrap();
+        assert_eq!(proof.ipp.l_vec.len(), 6);
+        assert_eq!(
+            proof.verify(params, &gens, b"balance", &commitments, 64),
+            Ok(true)
+        );
+        assert_eq!(
+            proof.verify(params, &gens, b"other", &commitments, 64),
+            Ok(false)
+        );
+
+        let aggregated = openings(&[0, 1, 1 << 31, 12345]);
+        let (proof, commitments) =
+            RangeProof::prove(params, &gens, b"balance", &aggregated, 32, &mut OsRng).unwrap();
+        assert_eq!(
+            proof.verify(params, &gens, b"balance", &commitments, 32),
+            Ok(true)
+        );
+
+        let bytes = RangeProof::to(&proof);
+        let decoded = <RangeProof<G> as FromBytes<_>>::from(&bytes).unwrap();
+        assert_eq!(decoded, proof);
+        assert_eq!(
+            <RangeProof<G> as FromBytes<_>>::from(&bytes[..bytes.len() - 1]).unwrap_err(),
+            Error::InvalidLength {
+                expected: bytes.len(),
+                actual: bytes.len() - 1
+            }
+        );
+
+        let mut swapped = commitments.clone();
+        swapped.swap(0, 1);
+        assert_eq!(
+            proof.verify(params, &gens, b"balance", &swapped, 32),
+            Ok(false)
+        );
+        let mut tampered = proof.clone();
+        tampered.t_x += G::Scalar::ONE;
+        assert_eq!(
+            tampered.verify(params, &gens, b"balance", &commitments, 32),
+            Ok(false)
+        );
+    }
+
+    fn out_of_range<G: CurveBackend>(params: &GroupParams<G>) {
+        let gens = RangeProofGens::<G>::new(16);
+        assert_eq!(
+            RangeProof::prove(params, &gens, b"", &openings(&[256]), 8, &mut OsRng).unwrap_err(),
+            Error::InvalidWitness
+        );
+        let negative = vec![Opening::random(-G::Scalar::ONE, &mut OsRng)];
+        assert_eq!(
+            RangeProof::prove(params, &gens, b"", &negative, 8, &mut OsRng).unwrap_err(),
+            Error::InvalidWitness
+        );
+        assert!(matches!(
+            RangeProof::prove(params, &gens, b"", &openings(&[1, 2, 3]), 8, &mut OsRng),
+            Err(Error::InvalidRangeParams(_))
+        ));
+        assert!(matches!(
+            RangeProof::prove(params, &gens, b"", &openings(&[1]), 12, &mut OsRng),
+            Err(Error::InvalidRangeParams(_))
+        ));
+        assert!(matches!(
+            RangeProof::prove(params, &gens, b"", &openings(&[1, 2, 3, 4]), 8, &mut OsRng),
+            Err(Error::InvalidRangeParams(_))
+        ));
+    }
+
+    #[test]
+    fn pallas_range_proofs() {
+        range_proofs::<pallas::Point>(&PALLAS_GROUP_PARAMS);
+        out_of_range::<pallas::Point>(&PALLAS_GROUP_PARAMS);
+    }
+
+    #[test]
+    fn vesta_range_proofs() {
+        range_proofs::<vesta::Point>(&VESTA_GROUP_PARAMS);
+        out_of_range::<vesta::Point>(&VESTA_GROUP_PARAMS);
+    }
+}
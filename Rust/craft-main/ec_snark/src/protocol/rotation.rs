@@ -0,0 +1,179 @@
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::sigma::linear::{Linear, LinearRelation};
+use crate::protocol::sigma::{self, SigmaProof};
+use crate::protocol::GroupParams;
+use crate::secret::SecretScalar;
+use crate::Error;
+use rand_core::{CryptoRng, RngCore};
+
+/// Domain-separation context of rotation proofs, see [`prove_rotation`].
+pub const ROTATION_CONTEXT: &[u8] = b"ec_snark.rotation.v1";
+
+/// A proof of knowledge of both the old and the new secret of a rotation.
+pub type RotationProof<G> = SigmaProof<Linear<G>>;
+
+/// The registered values `(y1, y2) = (g^x, h^x)` of a secret `x`.
+pub type Credentials<G> = (G, G);
+
+/// The context a rotation of `user`'s credentials to a secret derived with `salt`
+/// is bound to, so that neither can be swapped in a replayed request.
+///
+/// `rotations` is the number of rotations the server has accepted for the user so
+/// far. It only grows, so a recorded proof cannot be replayed later, not even once
+/// the account is back at the same credentials and salt.
+pub fn rotation_context(user: &str, salt: &[u8], rotations: u64) -> Vec<u8> {
+    let mut context = (user.len() as u64).to_le_bytes().to_vec();
+    context.extend_from_slice(user.as_bytes());
+    context.extend_from_slice(&rotations.to_le_bytes());
+    context.extend_from_slice(salt);
+    context
+}
+
+/// The statement `y1 = g^x, y2 = h^x, y1' = g^x', y2' = h^x'`.
+fn rotation_relation<G: CurveBackend>(
+    params: &GroupParams<G>,
+    old: &Credentials<G>,
+    new: &Credentials<G>,
+) -> Result<LinearRelation<G>, Error> {
+    if [old.0, old.1, new.0, new.1]
+        .iter()
+        .any(|p| bool::from(p.is_identity()))
+    {
+        return Err(Error::IdentityPoint);
+    }
+    let (g, h) = (params.g, params.h);
+    let ((y1, y2), (y1_new, y2_new)) = (*old, *new);
+    Ok(crate::linear_relation! {
+        scalars: [x, x_new],
+        y1 = x * g,
+        y2 = x * h,
+        y1_new = x_new * g,
+        y2_new = x_new * h,
+    })
+}
+
+/// Proves knowledge of the old secret and binds the credentials of the new one.
+///
+/// Both secrets are proven in a single transcript, so the proof authorizes exactly
+/// this change of credentials and cannot be replayed for other new credentials.
+///
+/// # Arguments
+/// * `params` - Group parameters of the curve.
+/// * `context` - Application data the proof is bound to, e.g. the username and new salt.
+/// * `old` - The secret behind the stored credentials.
+/// * `new` - The secret to be registered instead.
+/// * `rng` - The source of the prover's randomness.
+///
+/// # Returns
+/// The new credentials and the proof, or [`Error::IdentityPoint`] if a secret is zero.
+pub fn prove_rotation<G: CurveBackend, R: RngCore + CryptoRng>(
+    params: &GroupParams<G>,
+    context: &[u8],
+    old: &SecretScalar<G::Scalar>,
+    new: &SecretScalar<G::Scalar>,
+    rng: &mut R,
+) -> Result<(Credentials<G>, RotationProof<G>), Error> {
+    let credentials =
+        |x: &SecretScalar<G::Scalar>| (params.g * x.expose_secret(), params.h * x.expose_secret());
+    let new_credentials = credentials(new);
+    let relation = rotation_relation(params, &credentials(old), &new_credentials)?;
+    let witness = vec![
+        SecretScalar::new(*old.expose_secret()),
+        SecretScalar::new(*new.expose_secret()),
+    ];
+    let proof = sigma::prove::<Linear<G>, R>(
+        &[ROTATION_CONTEXT, context].concat(),
+        &relation,
+        &witness,
+        rng,
+    )?;
+    Ok((new_credentials, proof))
+}
+
+/// Verifies a proof produced by [`prove_rotation`] with the same context.
+///
+/// # Returns
+/// Whether the proof is valid for the stored and the new credentials, or
+/// [`Error::IdentityPoint`] if one of them contains the identity.
+pub fn verify_rotation<G: CurveBackend>(
+    params: &GroupParams<G>,
+    context: &[u8],
+    old: &Credentials<G>,
+    new: &Credentials<G>,
+    proof: &RotationProof<G>,
+) -> Result<bool, Error> {
+    let relation = rotation_relation(params, old, new)?;
+    sigma::verify(&[ROTATION_CONTEXT, context].concat(), &relation, proof)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{FromBytes, IntoBytes};
+    use crate::protocol::constants::{PALLAS_GROUP_PARAMS, RISTRETTO_GROUP_PARAMS};
+    use curve25519_dalek::ristretto::RistrettoPoint;
+    use pasta_curves::group::ff::Field;
+    use pasta_curves::pallas;
+    use rand_core::OsRng;
+
+    fn rotation<G>(params: &GroupParams<G>)
+    where
+        G: CurveBackend + FromBytes<G>,
+        G::Scalar: IntoBytes<G::Scalar> + FromBytes<G::Scalar>,
+    {
+        let old = SecretScalar::random(&mut OsRng);
+        let new = SecretScalar::random(&mut OsRng);
+        let stored = (
+            params.g * old.expose_secret(),
+            params.h * old.expose_secret(),
+        );
+        let (credentials, proof) =
+            prove_rotation(params, b"peggy", &old, &new, &mut OsRng).unwrap();
+        assert_eq!(credentials.0, params.g * new.expose_secret());
+        assert_eq!(
+            verify_rotation(params, b"peggy", &stored, &credentials, &proof),
+            Ok(true)
+        );
+        assert_eq!(
+            verify_rotation(params, b"victor", &stored, &credentials, &proof),
+            Ok(false)
+        );
+
+        // The proof does not authorize other new credentials.
+        let other = (params.g * G::Scalar::ONE, params.h * G::Scalar::ONE);
+        assert_eq!(
+            verify_rotation(params, b"peggy", &stored, &other, &proof),
+            Ok(false)
+        );
+
+        // Without the old secret, a proof for the stored credentials cannot be made.
+        let guess = SecretScalar::random(&mut OsRng);
+        let (credentials, proof) =
+            prove_rotation(params, b"peggy", &guess, &new, &mut OsRng).unwrap();
+        assert_eq!(
+            verify_rotation(params, b"peggy", &stored, &credentials, &proof),
+            Ok(false)
+        );
+
+        let bytes = RotationProof::to(&proof);
+        let decoded = <RotationProof<G> as FromBytes<_>>::from(&bytes).unwrap();
+        assert_eq!(decoded.commitment, proof.commitment);
+        assert_eq!(decoded.response, proof.response);
+
+        let zero = SecretScalar::new(G::Scalar::ZERO);
+        assert_eq!(
+            prove_rotation(params, b"peggy", &old, &zero, &mut OsRng).unwrap_err(),
+            Error::IdentityPoint
+        );
+    }
+
+    #[test]
+    fn pallas_rotation() {
+        rotation::<pallas::Point>(&PALLAS_GROUP_PARAMS);
+    }
+
+    #[test]
+    fn ristretto_rotation() {
+        rotation::<RistrettoPoint>(&RISTRETTO_GROUP_PARAMS);
+    }
+}
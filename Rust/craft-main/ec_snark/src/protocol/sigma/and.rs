@@ -0,0 +1,112 @@
+use crate::protocol::sigma::{SigmaChallenge, SigmaProtocol};
+use crate::protocol::transcript::Transcript;
+use crate::Error;
+use rand_core::{CryptoRng, RngCore};
+use std::marker::PhantomData;
+
+/// Proves knowledge of witnesses for both statements, under one shared challenge.
+pub struct And<A, B> {
+    _protocols: PhantomData<(A, B)>,
+}
+
+impl<A, B> SigmaProtocol for And<A, B>
+where
+    A: SigmaProtocol,
+    B: SigmaProtocol<Group = A::Group>,
+{
+    type Group = A::Group;
+    type Statement = (A::Statement, B::Statement);
+    type Witness = (A::Witness, B::Witness);
+    type Commitment = (A::Commitment, B::Commitment);
+    type State = (A::State, B::State);
+    type Response = (A::Response, B::Response);
+
+    fn commit<R: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::State), Error> {
+        let (a_commitment, a_state) = A::commit(&statement.0, &witness.0, rng)?;
+        let (b_commitment, b_state) = B::commit(&statement.1, &witness.1, rng)?;
+        Ok(((a_commitment, b_commitment), (a_state, b_state)))
+    }
+
+    fn respond(
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+        state: Self::State,
+        challenge: &SigmaChallenge<Self>,
+    ) -> Result<Self::Response, Error> {
+        Ok((
+            A::respond(&statement.0, &witness.0, state.0, challenge)?,
+            B::respond(&statement.1, &witness.1, state.1, challenge)?,
+        ))
+    }
+
+    fn verify(
+        statement: &Self::Statement,
+        commitment: &Self::Commitment,
+        challenge: &SigmaChallenge<Self>,
+        response: &Self::Response,
+    ) -> Result<bool, Error> {
+        let a = A::verify(&statement.0, &commitment.0, challenge, &response.0)?;
+        let b = B::verify(&statement.1, &commitment.1, challenge, &response.1)?;
+        Ok(a && b)
+    }
+
+    fn simulate<R: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        challenge: &SigmaChallenge<Self>,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::Response), Error> {
+        let (a_commitment, a_response) = A::simulate(&statement.0, challenge, rng)?;
+        let (b_commitment, b_response) = B::simulate(&statement.1, challenge, rng)?;
+        Ok(((a_commitment, b_commitment), (a_response, b_response)))
+    }
+
+    fn append_statement(transcript: &mut Transcript, statement: &Self::Statement) {
+        transcript.append_message(b"relation", b"and");
+        A::append_statement(transcript, &statement.0);
+        B::append_statement(transcript, &statement.1);
+    }
+
+    fn append_commitment(transcript: &mut Transcript, commitment: &Self::Commitment) {
+        A::append_commitment(transcript, &commitment.0);
+        B::append_commitment(transcript, &commitment.1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::sigma::schnorr::{Schnorr, SchnorrStatement};
+    use crate::protocol::sigma::{prove, verify};
+    use crate::secret::SecretScalar;
+    use pasta_curves::group::Group;
+    use pasta_curves::pallas::Point;
+    use rand_core::OsRng;
+
+    type BothKeys = And<Schnorr<Point>, Schnorr<Point>>;
+
+    #[test]
+    fn and_requires_both_witnesses() {
+        let x1 = SecretScalar::random(&mut OsRng);
+        let x2 = SecretScalar::random(&mut OsRng);
+        let statement = (
+            SchnorrStatement::new(Point::generator(), &x1),
+            SchnorrStatement::new(Point::generator(), &x2),
+        );
+        let witness = (x1, x2);
+        let proof = prove::<BothKeys, _>(b"test", &statement, &witness, &mut OsRng).unwrap();
+        assert_eq!(verify(b"test", &statement, &proof), Ok(true));
+        assert_eq!(verify(b"other", &statement, &proof), Ok(false));
+
+        let swapped = (statement.1, statement.0);
+        assert_eq!(verify(b"test", &swapped, &proof), Ok(false));
+        let wrong = (witness.1, witness.0);
+        assert_eq!(
+            prove::<BothKeys, _>(b"test", &statement, &wrong, &mut OsRng).unwrap_err(),
+            Error::InvalidWitness
+        );
+    }
+}
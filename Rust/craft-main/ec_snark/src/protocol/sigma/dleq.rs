@@ -0,0 +1,148 @@
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::sigma::linear::{Linear, LinearRelation};
+use crate::protocol::sigma::SigmaProtocol;
+use crate::protocol::transcript::Transcript;
+use crate::protocol::GroupParams;
+use crate::secret::{Nonce, SecretScalar};
+use crate::Error;
+use pasta_curves::group::ff::Field;
+use rand_core::{CryptoRng, RngCore};
+use std::marker::PhantomData;
+
+/// The DLEQ relation of the authentication protocol as a composable sigma protocol.
+///
+/// The interactive [`DleqEllipticCurve`](crate::protocol::elliptic_curves::dleq::DleqEllipticCurve)
+/// runs this protocol for the generators of its group parameters.
+/// The proof itself is the [`Linear`] proof of [`DleqStatement::relation`].
+pub struct Dleq<G> {
+    _group: PhantomData<G>,
+}
+
+/// The public statement of a DLEQ proof: `log_g(y1) == log_h(y2)`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DleqStatement<G> {
+    /// The first base.
+    pub g: G,
+    /// The second base.
+    pub h: G,
+    /// The public value `y1 = g^x`.
+    pub y1: G,
+    /// The public value `y2 = h^x`.
+    pub y2: G,
+}
+
+impl<G: CurveBackend> DleqStatement<G> {
+    /// Creates the statement for the generators of `params` and a secret `x`.
+    pub fn new(params: &GroupParams<G>, x: &SecretScalar<G::Scalar>) -> Self {
+        Self {
+            g: params.g,
+            h: params.h,
+            y1: params.g * x.expose_secret(),
+            y2: params.h * x.expose_secret(),
+        }
+    }
+
+    /// The statement as the linear relation `PK{(x): y1 = x·g ∧ y2 = x·h}`.
+    pub fn relation(&self) -> LinearRelation<G> {
+        let DleqStatement { g, h, y1, y2 } = *self;
+        crate::linear_relation! {
+            scalars: [x],
+            y1 = x * g,
+            y2 = x * h,
+        }
+    }
+}
+
+fn check_statement<G: CurveBackend>(statement: &DleqStatement<G>) -> Result<(), Error> {
+    let DleqStatement { g, h, y1, y2 } = statement;
+    if [g, h, y1, y2].iter().any(|p| bool::from(p.is_identity())) {
+        return Err(Error::IdentityPoint);
+    }
+    Ok(())
+}
+
+/// Copies the DLEQ witness into the one-element witness of the linear relation.
+fn linear_witness<F: Field>(witness: &SecretScalar<F>) -> Vec<SecretScalar<F>> {
+    vec![SecretScalar::new(*witness.expose_secret())]
+}
+
+impl<G: CurveBackend> SigmaProtocol for Dleq<G> {
+    type Group = G;
+    type Statement = DleqStatement<G>;
+    type Witness = SecretScalar<G::Scalar>;
+    type Commitment = (G, G);
+    type State = Nonce<G::Scalar>;
+    type Response = G::Scalar;
+
+    /// Commits to `(r1, r2) = (g^k, h^k)` for a fresh nonce `k`.
+    fn commit<R: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+        rng: &mut R,
+    ) -> Result<((G, G), Nonce<G::Scalar>), Error> {
+        check_statement(statement)?;
+        let (commitment, mut state) =
+            Linear::commit(&statement.relation(), &linear_witness(witness), rng)?;
+        Ok(((commitment[0], commitment[1]), state.remove(0)))
+    }
+
+    /// Returns `s = k + c * x`.
+    fn respond(
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+        state: Nonce<G::Scalar>,
+        challenge: &G::Scalar,
+    ) -> Result<G::Scalar, Error> {
+        let response = Linear::respond(
+            &statement.relation(),
+            &linear_witness(witness),
+            vec![state],
+            challenge,
+        )?;
+        Ok(response[0])
+    }
+
+    /// Checks `g^s == r1 * y1^c` and `h^s == r2 * y2^c`.
+    fn verify(
+        statement: &Self::Statement,
+        commitment: &(G, G),
+        challenge: &G::Scalar,
+        response: &G::Scalar,
+    ) -> Result<bool, Error> {
+        check_statement(statement)?;
+        let (r1, r2) = commitment;
+        if bool::from(r1.is_identity()) || bool::from(r2.is_identity()) {
+            return Err(Error::IdentityPoint);
+        }
+        Linear::verify(
+            &statement.relation(),
+            &vec![*r1, *r2],
+            challenge,
+            &vec![*response],
+        )
+    }
+
+    /// Picks `s` at random and solves for `r1 = g^s * y1^-c` and `r2 = h^s * y2^-c`.
+    fn simulate<R: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        challenge: &G::Scalar,
+        rng: &mut R,
+    ) -> Result<((G, G), G::Scalar), Error> {
+        check_statement(statement)?;
+        let (commitment, response) = Linear::simulate(&statement.relation(), challenge, rng)?;
+        Ok(((commitment[0], commitment[1]), response[0]))
+    }
+
+    fn append_statement(transcript: &mut Transcript, statement: &Self::Statement) {
+        transcript.append_message(b"relation", b"dleq");
+        transcript.append_element(b"g", &statement.g);
+        transcript.append_element(b"h", &statement.h);
+        transcript.append_element(b"y1", &statement.y1);
+        transcript.append_element(b"y2", &statement.y2);
+    }
+
+    fn append_commitment(transcript: &mut Transcript, commitment: &(G, G)) {
+        transcript.append_element(b"r1", &commitment.0);
+        transcript.append_element(b"r2", &commitment.1);
+    }
+}
@@ -0,0 +1,411 @@
+use crate::common::{self, FromBytes, IntoBytes};
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::msm::{linear_combination, multiscalar_mul};
+use crate::protocol::sigma::{SigmaProof, SigmaProtocol};
+use crate::protocol::transcript::Transcript;
+use crate::secret::{Nonce, SecretScalar};
+use crate::Error;
+use pasta_curves::group::ff::Field;
+use rand_core::{CryptoRng, RngCore};
+use std::marker::PhantomData;
+
+/// A secret scalar of a [`LinearRelation`], created with [`LinearRelation::scalar`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ScalarVar(usize);
+
+impl ScalarVar {
+    /// The position of the scalar in the witness.
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// One equation `lhs = sum(x_i * P_i)` of a [`LinearRelation`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Equation<G> {
+    /// The public left-hand side.
+    pub lhs: G,
+    /// The secret scalars and public points of the right-hand side.
+    pub terms: Vec<(ScalarVar, G)>,
+}
+
+/// A system of linear equations over group elements, the statement of [`Linear`].
+///
+/// In Camenisch–Stadler notation, `PK{(x, r): Y1 = x·G + r·H ∧ Y2 = x·H2}` reads
+///
+/// ```
+/// # use ec_snark::linear_relation;
+/// # use pasta_curves::group::Group;
+/// # use pasta_curves::pallas::{Point, Scalar};
+/// # let (g, h, h2) = (Point::generator(), Point::generator().double(), -Point::generator());
+/// # let (y1, y2) = (g * Scalar::from(2) + h * Scalar::from(3), h2 * Scalar::from(2));
+/// let relation = linear_relation! {
+///     scalars: [x, r],
+///     y1 = x * g + r * h,
+///     y2 = x * h2,
+/// };
+/// ```
+///
+/// or, with the builder API,
+///
+/// ```
+/// # use ec_snark::protocol::sigma::linear::LinearRelation;
+/// # use pasta_curves::group::Group;
+/// # use pasta_curves::pallas::{Point, Scalar};
+/// # let (g, h, h2) = (Point::generator(), Point::generator().double(), -Point::generator());
+/// # let (y1, y2) = (g * Scalar::from(2) + h * Scalar::from(3), h2 * Scalar::from(2));
+/// let mut relation = LinearRelation::new();
+/// let x = relation.scalar("x");
+/// let r = relation.scalar("r");
+/// relation.equation(y1, &[(x, g), (r, h)]);
+/// relation.equation(y2, &[(x, h2)]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinearRelation<G> {
+    scalars: Vec<String>,
+    equations: Vec<Equation<G>>,
+}
+
+impl<G> Default for LinearRelation<G> {
+    fn default() -> Self {
+        Self {
+            scalars: Vec::new(),
+            equations: Vec::new(),
+        }
+    }
+}
+
+impl<G: CurveBackend> LinearRelation<G> {
+    /// Creates a relation without scalars and equations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocates a secret scalar; the witness lists the scalars in allocation order.
+    pub fn scalar(&mut self, name: &str) -> ScalarVar {
+        self.scalars.push(name.to_string());
+        ScalarVar(self.scalars.len() - 1)
+    }
+
+    /// Adds the equation `lhs = sum(x_i * P_i)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a scalar was not allocated by this relation.
+    pub fn equation(&mut self, lhs: G, terms: &[(ScalarVar, G)]) -> &mut Self {
+        assert!(
+            terms.iter().all(|(x, _)| x.0 < self.scalars.len()),
+            "Scalar not allocated by this relation"
+        );
+        self.equations.push(Equation {
+            lhs,
+            terms: terms.to_vec(),
+        });
+        self
+    }
+
+    /// The names of the secret scalars, in witness order.
+    pub fn scalars(&self) -> &[String] {
+        &self.scalars
+    }
+
+    /// The equations of the relation.
+    pub fn equations(&self) -> &[Equation<G>] {
+        &self.equations
+    }
+
+    /// Evaluates the right-hand side of every equation for the given scalars in
+    /// constant time, for witnesses and nonces.
+    fn evaluate(&self, scalars: &[G::Scalar]) -> Vec<G> {
+        self.evaluate_with(scalars, linear_combination)
+    }
+
+    /// Evaluates the right-hand side of every equation for public scalars, with the
+    /// faster variable-time [`multiscalar_mul`].
+    fn evaluate_vartime(&self, scalars: &[G::Scalar]) -> Vec<G> {
+        self.evaluate_with(scalars, multiscalar_mul)
+    }
+
+    fn evaluate_with(&self, scalars: &[G::Scalar], sum: fn(&[G::Scalar], &[G]) -> G) -> Vec<G> {
+        self.equations
+            .iter()
+            .map(|equation| {
+                let (xs, points): (Vec<_>, Vec<_>) = equation
+                    .terms
+                    .iter()
+                    .map(|(x, point)| (scalars[x.0], *point))
+                    .unzip();
+                sum(&xs, &points)
+            })
+            .collect()
+    }
+}
+
+/// The sigma protocol proving knowledge of a witness for any [`LinearRelation`].
+///
+/// The prover commits to the relation evaluated at one nonce per scalar, and answers
+/// with `s_i = k_i + c * x_i`. The verifier checks that the relation evaluated at the
+/// responses equals `A_j + c * lhs_j` for every equation `j`.
+pub struct Linear<G> {
+    _group: PhantomData<G>,
+}
+
+impl<G: CurveBackend> SigmaProtocol for Linear<G> {
+    type Group = G;
+    type Statement = LinearRelation<G>;
+    type Witness = Vec<SecretScalar<G::Scalar>>;
+    type Commitment = Vec<G>;
+    type State = Vec<Nonce<G::Scalar>>;
+    type Response = Vec<G::Scalar>;
+
+    fn commit<R: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+        rng: &mut R,
+    ) -> Result<(Vec<G>, Vec<Nonce<G::Scalar>>), Error> {
+        if witness.len() != statement.scalars.len() {
+            return Err(Error::InvalidWitness);
+        }
+        let xs: Vec<_> = witness.iter().map(|x| *x.expose_secret()).collect();
+        let holds = statement
+            .evaluate(&xs)
+            .iter()
+            .zip(&statement.equations)
+            .all(|(image, equation)| *image == equation.lhs);
+        if !holds {
+            return Err(Error::InvalidWitness);
+        }
+        let nonces: Vec<_> = witness.iter().map(|_| Nonce::random(rng)).collect();
+        let ks: Vec<_> = nonces.iter().map(|k| *k.expose_secret()).collect();
+        Ok((statement.evaluate(&ks), nonces))
+    }
+
+    fn respond(
+        _: &Self::Statement,
+        witness: &Self::Witness,
+        state: Vec<Nonce<G::Scalar>>,
+        challenge: &G::Scalar,
+    ) -> Result<Vec<G::Scalar>, Error> {
+        if state.len() != witness.len() {
+            return Err(Error::InvalidWitness);
+        }
+        Ok(state
+            .into_iter()
+            .zip(witness)
+            .map(|(k, x)| k.consume() + *challenge * x.expose_secret())
+            .collect())
+    }
+
+    fn verify(
+        statement: &Self::Statement,
+        commitment: &Vec<G>,
+        challenge: &G::Scalar,
+        response: &Vec<G::Scalar>,
+    ) -> Result<bool, Error> {
+        if commitment.len() != statement.equations.len()
+            || response.len() != statement.scalars.len()
+        {
+            return Ok(false);
+        }
+        Ok(statement
+            .evaluate_vartime(response)
+            .iter()
+            .zip(commitment)
+            .zip(&statement.equations)
+            .all(|((image, a), equation)| *image == *a + equation.lhs * challenge))
+    }
+
+    fn simulate<R: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        challenge: &G::Scalar,
+        rng: &mut R,
+    ) -> Result<(Vec<G>, Vec<G::Scalar>), Error> {
+        let response: Vec<_> = statement
+            .scalars
+            .iter()
+            .map(|_| <G::Scalar as Field>::random(&mut *rng))
+            .collect();
+        let commitment = statement
+            .evaluate(&response)
+            .into_iter()
+            .zip(&statement.equations)
+            .map(|(image, equation)| image - equation.lhs * challenge)
+            .collect();
+        Ok((commitment, response))
+    }
+
+    fn append_statement(transcript: &mut Transcript, statement: &Self::Statement) {
+        transcript.append_message(b"relation", b"linear");
+        transcript.append_message(b"scalars", &(statement.scalars.len() as u64).to_le_bytes());
+        transcript.append_message(
+            b"equations",
+            &(statement.equations.len() as u64).to_le_bytes(),
+        );
+        for equation in &statement.equations {
+            transcript.append_element(b"lhs", &equation.lhs);
+            transcript.append_message(b"terms", &(equation.terms.len() as u64).to_le_bytes());
+            for (x, point) in &equation.terms {
+                transcript.append_message(b"scalar", &(x.0 as u64).to_le_bytes());
+                transcript.append_element(b"point", point);
+            }
+        }
+    }
+
+    fn append_commitment(transcript: &mut Transcript, commitment: &Vec<G>) {
+        for a in commitment {
+            transcript.append_element(b"a", a);
+        }
+    }
+}
+
+impl<G> IntoBytes<SigmaProof<Linear<G>>> for SigmaProof<Linear<G>>
+where
+    G: CurveBackend,
+    G::Scalar: IntoBytes<G::Scalar>,
+{
+    /// Encodes the numbers of commitments and responses as big-endian `u16`s,
+    /// followed by the commitments and the responses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the proof has more than `u16::MAX` commitments or responses.
+    fn to(t: &SigmaProof<Linear<G>>) -> Vec<u8> {
+        let count = |n: usize| u16::try_from(n).expect("Too many parts in linear proof");
+        let counts = [count(t.commitment.len()), count(t.response.len())];
+        let mut parts = vec![counts.iter().flat_map(|n| n.to_be_bytes()).collect()];
+        parts.extend(t.commitment.iter().map(G::to));
+        parts.extend(t.response.iter().map(G::Scalar::to));
+        common::encode_parts(&parts)
+    }
+}
+
+impl<G> FromBytes<SigmaProof<Linear<G>>> for SigmaProof<Linear<G>>
+where
+    G: CurveBackend + FromBytes<G>,
+    G::Scalar: FromBytes<G::Scalar>,
+{
+    fn from(bytes: &[u8]) -> Result<SigmaProof<Linear<G>>, Error> {
+        let (m, n) = match bytes {
+            [0, 4, m1, m0, n1, n0, ..] => (
+                u16::from_be_bytes([*m1, *m0]) as usize,
+                u16::from_be_bytes([*n1, *n0]) as usize,
+            ),
+            _ => return Err(Error::NonCanonicalEncoding),
+        };
+        let parts = common::decode_parts(bytes, 1 + m + n)?;
+        Ok(SigmaProof {
+            commitment: parts[1..=m]
+                .iter()
+                .map(|part| <G as FromBytes<G>>::from(part))
+                .collect::<Result<_, _>>()?,
+            response: parts[1 + m..]
+                .iter()
+                .map(|part| <G::Scalar as FromBytes<G::Scalar>>::from(part))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Builds a [`LinearRelation`] from Camenisch–Stadler style equations.
+///
+/// The scalars become [`ScalarVar`] bindings in the listed order; every other
+/// identifier must be a group element in scope. See [`LinearRelation`] for an example.
+#[macro_export]
+macro_rules! linear_relation {
+    (scalars: [$($scalar:ident),* $(,)?], $($lhs:ident = $x:ident * $point:ident $(+ $xs:ident * $points:ident)*),+ $(,)?) => {{
+        let mut relation = $crate::protocol::sigma::linear::LinearRelation::new();
+        $(let $scalar = relation.scalar(stringify!($scalar));)*
+        $(relation.equation($lhs, &[($x, $point) $(, ($xs, $points))*]);)+
+        relation
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::sigma::{prove, random_challenge, verify};
+    use pasta_curves::group::Group;
+    use pasta_curves::pallas::{Point, Scalar};
+    use rand_core::OsRng;
+
+    fn pedersen_relation() -> (LinearRelation<Point>, Vec<SecretScalar<Scalar>>) {
+        let g = Point::generator();
+        let h = Point::hash_to_group("test", b"h");
+        let h2 = Point::hash_to_group("test", b"h2");
+        let (x, r) = (Scalar::random(OsRng), Scalar::random(OsRng));
+        let y1 = g * x + h * r;
+        let y2 = h2 * x;
+        let relation = crate::linear_relation! {
+            scalars: [x, r],
+            y1 = x * g + r * h,
+            y2 = x * h2,
+        };
+        (relation, vec![SecretScalar::new(x), SecretScalar::new(r)])
+    }
+
+    #[test]
+    fn macro_matches_builder() {
+        let (relation, _) = pedersen_relation();
+        assert_eq!(relation.scalars(), ["x", "r"]);
+        assert_eq!(relation.equations().len(), 2);
+        assert_eq!(relation.equations()[0].terms.len(), 2);
+        assert_eq!(relation.equations()[1].terms[0].0.index(), 0);
+    }
+
+    #[test]
+    fn linear_proof_verifies() {
+        let (relation, witness) = pedersen_relation();
+        let proof = prove::<Linear<Point>, _>(b"test", &relation, &witness, &mut OsRng).unwrap();
+        assert_eq!(verify(b"test", &relation, &proof), Ok(true));
+
+        let mut forged = proof.clone();
+        forged.response[1] += Scalar::ONE;
+        assert_eq!(verify(b"test", &relation, &forged), Ok(false));
+
+        let c = random_challenge::<Linear<Point>, _>(&mut OsRng);
+        let (a, s) = Linear::simulate(&relation, &c, &mut OsRng).unwrap();
+        assert_eq!(Linear::verify(&relation, &a, &c, &s), Ok(true));
+    }
+
+    #[test]
+    fn linear_proof_round_trips_through_bytes() {
+        let (relation, witness) = pedersen_relation();
+        let proof = prove::<Linear<Point>, _>(b"test", &relation, &witness, &mut OsRng).unwrap();
+        let bytes = SigmaProof::to(&proof);
+        let decoded = <SigmaProof<Linear<Point>> as FromBytes<_>>::from(&bytes).unwrap();
+        assert_eq!(decoded.commitment, proof.commitment);
+        assert_eq!(decoded.response, proof.response);
+        assert_eq!(verify(b"test", &relation, &decoded), Ok(true));
+        assert!(
+            <SigmaProof<Linear<Point>> as FromBytes<_>>::from(&bytes[..bytes.len() - 1]).is_err()
+        );
+
+        // Counts above 255 do not wrap around.
+        let large = SigmaProof::<Linear<Point>> {
+            commitment: vec![Point::generator(); 300],
+            response: vec![Scalar::ONE; 257],
+        };
+        let decoded =
+            <SigmaProof<Linear<Point>> as FromBytes<_>>::from(&SigmaProof::to(&large)).unwrap();
+        assert_eq!(decoded.commitment, large.commitment);
+        assert_eq!(decoded.response, large.response);
+    }
+
+    #[test]
+    fn linear_rejects_wrong_witness() {
+        let (relation, witness) = pedersen_relation();
+        let swapped = vec![
+            SecretScalar::new(*witness[1].expose_secret()),
+            SecretScalar::new(*witness[0].expose_secret()),
+        ];
+        assert_eq!(
+            Linear::commit(&relation, &swapped, &mut OsRng).unwrap_err(),
+            Error::InvalidWitness
+        );
+        let truncated = vec![SecretScalar::new(*witness[0].expose_secret())];
+        assert_eq!(
+            Linear::commit(&relation, &truncated, &mut OsRng).unwrap_err(),
+            Error::InvalidWitness
+        );
+    }
+}
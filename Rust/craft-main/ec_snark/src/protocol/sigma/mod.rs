@@ -0,0 +1,174 @@
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::transcript::Transcript;
+use crate::Error;
+use pasta_curves::group::ff::Field;
+use rand_core::{CryptoRng, RngCore};
+
+pub mod and;
+pub mod dleq;
+pub mod linear;
+pub mod or;
+pub mod schnorr;
+
+/// Domain-separation label of Fiat–Shamir transformed sigma proofs.
+pub const SIGMA_DOMAIN: &[u8] = b"ec_snark.sigma.v1";
+
+/// Challenge type of a sigma protocol: a scalar of its group.
+pub type SigmaChallenge<P> = <<P as SigmaProtocol>::Group as pasta_curves::group::Group>::Scalar;
+
+/// A three-move public-coin proof of knowledge: commitment, challenge, response.
+///
+/// Implementations are stateless; every message is passed explicitly, so protocols
+/// can be composed with [`and::And`] and [`or::Or`] and made non-interactive with
+/// [`prove`] and [`verify`].
+pub trait SigmaProtocol {
+    /// The group the relation is defined over.
+    type Group: CurveBackend;
+    /// The public statement.
+    type Statement;
+    /// The prover's secret.
+    type Witness;
+    /// The prover's first message.
+    type Commitment;
+    /// The prover's randomness between the commitment and the response.
+    type State;
+    /// The prover's last message.
+    type Response;
+
+    /// Computes the commitment for a statement and its witness.
+    ///
+    /// # Returns
+    /// The commitment and the state needed to respond, or [`Error::InvalidWitness`]
+    /// if the witness does not satisfy the statement.
+    fn commit<R: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::State), Error>;
+
+    /// Computes the response to a challenge, consuming the prover's state.
+    fn respond(
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+        state: Self::State,
+        challenge: &SigmaChallenge<Self>,
+    ) -> Result<Self::Response, Error>;
+
+    /// Checks a transcript against the statement.
+    ///
+    /// # Returns
+    /// Whether the transcript is accepting, or an error if the statement or the
+    /// messages are malformed.
+    fn verify(
+        statement: &Self::Statement,
+        commitment: &Self::Commitment,
+        challenge: &SigmaChallenge<Self>,
+        response: &Self::Response,
+    ) -> Result<bool, Error>;
+
+    /// Produces an accepting transcript for a given challenge without the witness.
+    ///
+    /// This is the special honest-verifier zero-knowledge simulator; the OR
+    /// composition uses it for the branches the prover has no witness for.
+    fn simulate<R: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        challenge: &SigmaChallenge<Self>,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::Response), Error>;
+
+    /// Absorbs the statement into a Fiat–Shamir transcript.
+    fn append_statement(transcript: &mut Transcript, statement: &Self::Statement);
+
+    /// Absorbs the commitment into a Fiat–Shamir transcript.
+    fn append_commitment(transcript: &mut Transcript, commitment: &Self::Commitment);
+}
+
+/// A non-interactive sigma proof; the challenge is recomputed by the verifier.
+pub struct SigmaProof<P: SigmaProtocol> {
+    /// The prover's first message.
+    pub commitment: P::Commitment,
+    /// The prover's last message.
+    pub response: P::Response,
+}
+
+impl<P: SigmaProtocol> Clone for SigmaProof<P>
+where
+    P::Commitment: Clone,
+    P::Response: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            commitment: self.commitment.clone(),
+            response: self.response.clone(),
+        }
+    }
+}
+
+impl<P: SigmaProtocol> std::fmt::Debug for SigmaProof<P>
+where
+    P::Commitment: std::fmt::Debug,
+    P::Response: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SigmaProof")
+            .field("commitment", &self.commitment)
+            .field("response", &self.response)
+            .finish()
+    }
+}
+
+/// Computes the Fiat–Shamir challenge of a sigma protocol transcript.
+///
+/// The domain label, the caller's context, the curve name, the statement and the
+/// commitment are absorbed in this order.
+pub fn challenge<P: SigmaProtocol>(
+    context: &[u8],
+    statement: &P::Statement,
+    commitment: &P::Commitment,
+) -> SigmaChallenge<P> {
+    let mut transcript = Transcript::new(SIGMA_DOMAIN);
+    transcript.append_message(b"context", context);
+    transcript.append_message(b"curve", P::Group::CURVE.to_string().as_bytes());
+    P::append_statement(&mut transcript, statement);
+    P::append_commitment(&mut transcript, commitment);
+    transcript.challenge_scalar::<P::Group>(b"c")
+}
+
+/// Produces a non-interactive proof with the Fiat–Shamir transform.
+///
+/// # Arguments
+/// * `context` - Application data the proof is bound to, e.g. a session identifier.
+/// * `statement` - The public statement.
+/// * `witness` - The prover's secret.
+/// * `rng` - The source of the prover's randomness.
+pub fn prove<P: SigmaProtocol, R: RngCore + CryptoRng>(
+    context: &[u8],
+    statement: &P::Statement,
+    witness: &P::Witness,
+    rng: &mut R,
+) -> Result<SigmaProof<P>, Error> {
+    let (commitment, state) = P::commit(statement, witness, rng)?;
+    let c = challenge::<P>(context, statement, &commitment);
+    let response = P::respond(statement, witness, state, &c)?;
+    Ok(SigmaProof {
+        commitment,
+        response,
+    })
+}
+
+/// Verifies a proof produced by [`prove`] with the same context.
+pub fn verify<P: SigmaProtocol>(
+    context: &[u8],
+    statement: &P::Statement,
+    proof: &SigmaProof<P>,
+) -> Result<bool, Error> {
+    let c = challenge::<P>(context, statement, &proof.commitment);
+    P::verify(statement, &proof.commitment, &c, &proof.response)
+}
+
+/// Draws a random challenge for the interactive protocol.
+pub fn random_challenge<P: SigmaProtocol, R: RngCore + CryptoRng>(
+    rng: &mut R,
+) -> SigmaChallenge<P> {
+    <SigmaChallenge<P> as Field>::random(rng)
+}
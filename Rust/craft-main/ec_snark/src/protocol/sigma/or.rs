@@ -0,0 +1,279 @@
+use crate::protocol::sigma::{SigmaChallenge, SigmaProtocol};
+use crate::protocol::transcript::Transcript;
+use crate::Error;
+use pasta_curves::group::ff::Field;
+use rand_core::{CryptoRng, RngCore};
+use std::marker::PhantomData;
+
+/// Cramer–Damgård–Schoenmakers OR-composition: proves knowledge of a witness for
+/// one of several statements without revealing which.
+///
+/// The prover simulates every branch it has no witness for with a random challenge,
+/// and answers the real branch with the verifier's challenge minus their sum.
+pub struct Or<P> {
+    _protocol: PhantomData<P>,
+}
+
+/// The witness of an [`Or`] proof: the index of the known branch and its witness.
+pub struct OrWitness<W> {
+    /// The index of the statement the witness belongs to.
+    pub index: usize,
+    /// The witness of that statement.
+    pub witness: W,
+}
+
+/// The prover state of an [`Or`] proof.
+pub struct OrState<P: SigmaProtocol> {
+    state: P::State,
+    simulated: Vec<Option<(SigmaChallenge<P>, P::Response)>>,
+}
+
+/// The response of an [`Or`] proof: one challenge and one response per branch.
+pub struct OrResponse<P: SigmaProtocol> {
+    /// The challenges of the branches, summing up to the verifier's challenge.
+    pub challenges: Vec<SigmaChallenge<P>>,
+    /// The responses of the branches.
+    pub responses: Vec<P::Response>,
+}
+
+impl<P: SigmaProtocol> Clone for OrResponse<P>
+where
+    P::Response: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            challenges: self.challenges.clone(),
+            responses: self.responses.clone(),
+        }
+    }
+}
+
+impl<P: SigmaProtocol> std::fmt::Debug for OrResponse<P>
+where
+    P::Response: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OrResponse")
+            .field("challenges", &self.challenges)
+            .field("responses", &self.responses)
+            .finish()
+    }
+}
+
+impl<P: SigmaProtocol> SigmaProtocol for Or<P> {
+    type Group = P::Group;
+    type Statement = Vec<P::Statement>;
+    type Witness = OrWitness<P::Witness>;
+    type Commitment = Vec<P::Commitment>;
+    type State = OrState<P>;
+    type Response = OrResponse<P>;
+
+    fn commit<R: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::State), Error> {
+        if witness.index >= statement.len() {
+            return Err(Error::InvalidWitness);
+        }
+        let mut commitments = Vec::with_capacity(statement.len());
+        let mut simulated = Vec::with_capacity(statement.len());
+        let mut real_state = None;
+        for (j, branch) in statement.iter().enumerate() {
+            if j == witness.index {
+                let (commitment, state) = P::commit(branch, &witness.witness, rng)?;
+                commitments.push(commitment);
+                real_state = Some(state);
+                simulated.push(None);
+            } else {
+                let c = <SigmaChallenge<P> as Field>::random(&mut *rng);
+                let (commitment, response) = P::simulate(branch, &c, rng)?;
+                commitments.push(commitment);
+                simulated.push(Some((c, response)));
+            }
+        }
+        let state = real_state.ok_or(Error::InvalidWitness)?;
+        Ok((commitments, OrState { state, simulated }))
+    }
+
+    fn respond(
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+        state: Self::State,
+        challenge: &SigmaChallenge<Self>,
+    ) -> Result<Self::Response, Error> {
+        let OrState { state, simulated } = state;
+        let real = *challenge
+            - simulated
+                .iter()
+                .flatten()
+                .map(|(c, _)| *c)
+                .sum::<SigmaChallenge<P>>();
+        let mut real_response = Some(P::respond(
+            &statement[witness.index],
+            &witness.witness,
+            state,
+            &real,
+        )?);
+
+        let mut challenges = Vec::with_capacity(simulated.len());
+        let mut responses = Vec::with_capacity(simulated.len());
+        for branch in simulated {
+            let (c, response) = match branch {
+                Some(simulated) => simulated,
+                None => (real, real_response.take().ok_or(Error::InvalidWitness)?),
+            };
+            challenges.push(c);
+            responses.push(response);
+        }
+        Ok(OrResponse {
+            challenges,
+            responses,
+        })
+    }
+
+    /// Checks that the branch challenges sum up to `challenge` and every branch verifies.
+    fn verify(
+        statement: &Self::Statement,
+        commitment: &Self::Commitment,
+        challenge: &SigmaChallenge<Self>,
+        response: &Self::Response,
+    ) -> Result<bool, Error> {
+        let n = statement.len();
+        if n == 0
+            || commitment.len() != n
+            || response.challenges.len() != n
+            || response.responses.len() != n
+        {
+            return Ok(false);
+        }
+        if response
+            .challenges
+            .iter()
+            .copied()
+            .sum::<SigmaChallenge<P>>()
+            != *challenge
+        {
+            return Ok(false);
+        }
+        for j in 0..n {
+            let c = &response.challenges[j];
+            if !P::verify(&statement[j], &commitment[j], c, &response.responses[j])? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn simulate<R: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        challenge: &SigmaChallenge<Self>,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::Response), Error> {
+        let n = statement.len();
+        let mut challenges: Vec<SigmaChallenge<P>> = (1..n)
+            .map(|_| <SigmaChallenge<P> as Field>::random(&mut *rng))
+            .collect();
+        if n > 0 {
+            challenges.push(*challenge - challenges.iter().copied().sum::<SigmaChallenge<P>>());
+        }
+        let mut commitments = Vec::with_capacity(n);
+        let mut responses = Vec::with_capacity(n);
+        for (branch, c) in statement.iter().zip(&challenges) {
+            let (commitment, response) = P::simulate(branch, c, rng)?;
+            commitments.push(commitment);
+            responses.push(response);
+        }
+        Ok((
+            commitments,
+            OrResponse {
+                challenges,
+                responses,
+            },
+        ))
+    }
+
+    fn append_statement(transcript: &mut Transcript, statement: &Self::Statement) {
+        transcript.append_message(b"relation", b"or");
+        transcript.append_message(b"branches", &(statement.len() as u64).to_le_bytes());
+        for branch in statement {
+            P::append_statement(transcript, branch);
+        }
+    }
+
+    fn append_commitment(transcript: &mut Transcript, commitment: &Self::Commitment) {
+        for branch in commitment {
+            P::append_commitment(transcript, branch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::sigma::schnorr::{Schnorr, SchnorrStatement};
+    use crate::protocol::sigma::{prove, random_challenge, verify};
+    use crate::secret::SecretScalar;
+    use pasta_curves::group::Group;
+    use pasta_curves::pallas::Point;
+    use rand_core::OsRng;
+
+    type OneOfKeys = Or<Schnorr<Point>>;
+
+    fn statements(
+        n: usize,
+    ) -> (
+        Vec<SchnorrStatement<Point>>,
+        Vec<SecretScalar<pasta_curves::pallas::Scalar>>,
+    ) {
+        let keys: Vec<_> = (0..n).map(|_| SecretScalar::random(&mut OsRng)).collect();
+        let statements = keys
+            .iter()
+            .map(|x| SchnorrStatement::new(Point::generator(), x))
+            .collect();
+        (statements, keys)
+    }
+
+    #[test]
+    fn or_proves_any_branch() {
+        let (statement, keys) = statements(4);
+        for (index, x) in keys.into_iter().enumerate() {
+            let witness = OrWitness { index, witness: x };
+            let proof = prove::<OneOfKeys, _>(b"test", &statement, &witness, &mut OsRng).unwrap();
+            assert_eq!(verify(b"test", &statement, &proof), Ok(true));
+
+            let mut forged = proof.clone();
+            forged.response.challenges[0] += pasta_curves::pallas::Scalar::ONE;
+            assert_eq!(verify(b"test", &statement, &forged), Ok(false));
+        }
+    }
+
+    #[test]
+    fn or_rejects_unknown_witness() {
+        let (statement, _) = statements(3);
+        let stranger = OrWitness {
+            index: 1,
+            witness: SecretScalar::random(&mut OsRng),
+        };
+        assert!(prove::<OneOfKeys, _>(b"test", &statement, &stranger, &mut OsRng).is_err());
+        let out_of_range = OrWitness {
+            index: 3,
+            witness: SecretScalar::random(&mut OsRng),
+        };
+        assert_eq!(
+            prove::<OneOfKeys, _>(b"test", &statement, &out_of_range, &mut OsRng).unwrap_err(),
+            Error::InvalidWitness
+        );
+    }
+
+    #[test]
+    fn or_simulation_verifies() {
+        let (statement, _) = statements(3);
+        let c = random_challenge::<OneOfKeys, _>(&mut OsRng);
+        let (commitment, response) = OneOfKeys::simulate(&statement, &c, &mut OsRng).unwrap();
+        assert_eq!(
+            OneOfKeys::verify(&statement, &commitment, &c, &response),
+            Ok(true)
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::sigma::SigmaProtocol;
+use crate::protocol::transcript::Transcript;
+use crate::secret::{Nonce, SecretScalar};
+use crate::Error;
+use rand_core::{CryptoRng, RngCore};
+use std::marker::PhantomData;
+
+/// Schnorr's proof of knowledge of `x` such that `base^x = y`.
+pub struct Schnorr<G> {
+    _group: PhantomData<G>,
+}
+
+/// The public statement of a [`Schnorr`] proof.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SchnorrStatement<G> {
+    /// The base of the discrete logarithm.
+    pub base: G,
+    /// The public key `base^x`.
+    pub y: G,
+}
+
+impl<G: CurveBackend> SchnorrStatement<G> {
+    /// Creates the statement `base^x = y` for a secret `x`.
+    pub fn new(base: G, x: &SecretScalar<G::Scalar>) -> Self {
+        Self {
+            base,
+            y: base * x.expose_secret(),
+        }
+    }
+}
+
+fn check_statement<G: CurveBackend>(statement: &SchnorrStatement<G>) -> Result<(), Error> {
+    if bool::from(statement.base.is_identity()) || bool::from(statement.y.is_identity()) {
+        return Err(Error::IdentityPoint);
+    }
+    Ok(())
+}
+
+impl<G: CurveBackend> SigmaProtocol for Schnorr<G> {
+    type Group = G;
+    type Statement = SchnorrStatement<G>;
+    type Witness = SecretScalar<G::Scalar>;
+    type Commitment = G;
+    type State = Nonce<G::Scalar>;
+    type Response = G::Scalar;
+
+    /// Commits to `r = base^k` for a fresh nonce `k`.
+    fn commit<R: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+        rng: &mut R,
+    ) -> Result<(G, Nonce<G::Scalar>), Error> {
+        check_statement(statement)?;
+        if statement.base * witness.expose_secret() != statement.y {
+            return Err(Error::InvalidWitness);
+        }
+        let k = Nonce::random(rng);
+        Ok((statement.base * k.expose_secret(), k))
+    }
+
+    /// Returns `s = k + c * x`.
+    fn respond(
+        _: &Self::Statement,
+        witness: &Self::Witness,
+        state: Nonce<G::Scalar>,
+        challenge: &G::Scalar,
+    ) -> Result<G::Scalar, Error> {
+        Ok(state.consume() + *challenge * witness.expose_secret())
+    }
+
+    /// Checks `base^s == r * y^c`.
+    fn verify(
+        statement: &Self::Statement,
+        commitment: &G,
+        challenge: &G::Scalar,
+        response: &G::Scalar,
+    ) -> Result<bool, Error> {
+        check_statement(statement)?;
+        if bool::from(commitment.is_identity()) {
+            return Err(Error::IdentityPoint);
+        }
+        Ok(statement.base * response == *commitment + statement.y * challenge)
+    }
+
+    /// Picks `s` at random and solves for `r = base^s * y^-c`.
+    fn simulate<R: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        challenge: &G::Scalar,
+        rng: &mut R,
+    ) -> Result<(G, G::Scalar), Error> {
+        check_statement(statement)?;
+        let s = <G::Scalar as pasta_curves::group::ff::Field>::random(rng);
+        Ok((statement.base * s - statement.y * challenge, s))
+    }
+
+    fn append_statement(transcript: &mut Transcript, statement: &Self::Statement) {
+        transcript.append_message(b"relation", b"schnorr");
+        transcript.append_element(b"base", &statement.base);
+        transcript.append_element(b"y", &statement.y);
+    }
+
+    fn append_commitment(transcript: &mut Transcript, commitment: &G) {
+        transcript.append_element(b"r", commitment);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::sigma::{self, random_challenge};
+    use pasta_curves::group::Group;
+    use pasta_curves::pallas::Point;
+    use rand_core::OsRng;
+
+    #[test]
+    fn schnorr_interactive_and_simulated() {
+        let x = SecretScalar::random(&mut OsRng);
+        let statement = SchnorrStatement::new(Point::generator(), &x);
+        let (r, k) = Schnorr::commit(&statement, &x, &mut OsRng).unwrap();
+        let c = random_challenge::<Schnorr<Point>, _>(&mut OsRng);
+        let s = Schnorr::respond(&statement, &x, k, &c).unwrap();
+        assert_eq!(Schnorr::verify(&statement, &r, &c, &s), Ok(true));
+        assert_eq!(Schnorr::verify(&statement, &r, &(c + c), &s), Ok(false));
+
+        let (r, s) = Schnorr::simulate(&statement, &c, &mut OsRng).unwrap();
+        assert_eq!(Schnorr::verify(&statement, &r, &c, &s), Ok(true));
+    }
+
+    #[test]
+    fn schnorr_rejects_wrong_witness() {
+        let x = SecretScalar::random(&mut OsRng);
+        let statement = SchnorrStatement::new(Point::generator(), &x);
+        let other = SecretScalar::random(&mut OsRng);
+        assert_eq!(
+            sigma::prove::<Schnorr<Point>, _>(b"test", &statement, &other, &mut OsRng).unwrap_err(),
+            Error::InvalidWitness
+        );
+    }
+}
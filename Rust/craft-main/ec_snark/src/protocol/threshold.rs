@@ -0,0 +1,615 @@
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::sigma::dleq::{Dleq, DleqStatement};
+use crate::protocol::sigma::{self, SigmaProof};
+use crate::protocol::vss::{lagrange_coefficient, FeldmanCommitments, Share};
+use crate::protocol::GroupParams;
+use crate::secret::Nonce;
+use crate::Error;
+use pasta_curves::group::ff::Field;
+use rand_core::{CryptoRng, RngCore};
+
+/// Domain-separation context of the proofs attached to verification shares.
+pub const VERIFICATION_SHARE_CONTEXT: &[u8] = b"ec_snark.threshold.share.v1";
+
+/// Domain-separation prefix of the hash to the binding factors.
+pub const BINDING_FACTOR_DOMAIN: &str = "ec_snark.threshold.binding.v1";
+
+/// A shareholder of a secret split with [`vss::split`](crate::protocol::vss::split).
+///
+/// Any `t` signers jointly answer a DLEQ challenge in two rounds: each one
+/// [`commit`](Self::commit)s to a pair of nonces, and after the challenge is known each
+/// one [`respond`](Self::respond)s with a partial response. A [`ThresholdCoordinator`]
+/// combines both into the `(r1, r2)` and `s` of an ordinary DLEQ transcript.
+///
+/// As in FROST, every signer's nonce is `d_i + rho_i * e_i`, where the binding factor
+/// `rho_i` hashes the context and the commitments of all signers of the round. A
+/// commitment thus only fits the round it was made for, which rules out the ROS
+/// attacks on the Fiat–Shamir challenge that plain sums of nonces allow when rounds
+/// run concurrently.
+#[derive(Debug)]
+pub struct ThresholdSigner<G: CurveBackend> {
+    share: Share<G::Scalar>,
+    _group: std::marker::PhantomData<G>,
+}
+
+/// The public part `(g^x_i, h^x_i)` of a share, with a proof that both use the same `x_i`.
+#[derive(Clone, Debug)]
+pub struct VerificationShare<G: CurveBackend> {
+    /// The share index `i`.
+    pub index: u32,
+    /// `g^x_i`.
+    pub y1: G,
+    /// `h^x_i`.
+    pub y2: G,
+    /// The DLEQ proof of `log_g(y1) == log_h(y2)`.
+    pub proof: SigmaProof<Dleq<G>>,
+}
+
+/// A signer's first-round message, the commitments to its hiding nonce `d_i`
+/// and its binding nonce `e_i`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct NonceCommitment<G> {
+    /// The share index `i`.
+    pub index: u32,
+    /// `(g^d_i, h^d_i)`.
+    pub hiding: (G, G),
+    /// `(g^e_i, h^e_i)`.
+    pub binding: (G, G),
+}
+
+/// The nonces `d_i` and `e_i` a signer keeps between the two rounds.
+#[derive(Debug)]
+pub struct SigningNonces<G: CurveBackend> {
+    commitment: NonceCommitment<G>,
+    hiding: Nonce<G::Scalar>,
+    binding: Nonce<G::Scalar>,
+}
+
+/// A signer's second-round message `s_i = d_i + rho_i * e_i + c * lambda_i * x_i`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PartialResponse<F> {
+    /// The share index `i`.
+    pub index: u32,
+    /// The partial response.
+    pub s: F,
+}
+
+impl<G: CurveBackend> ThresholdSigner<G> {
+    /// Wraps a share of the secret.
+    pub fn new(share: Share<G::Scalar>) -> Self {
+        Self {
+            share,
+            _group: std::marker::PhantomData,
+        }
+    }
+
+    /// The index of the share.
+    pub fn index(&self) -> u32 {
+        self.share.index
+    }
+
+    /// Computes the verification share to hand to the coordinator once, at setup.
+    pub fn verification_share<R: RngCore + CryptoRng>(
+        &self,
+        params: &GroupParams<G>,
+        rng: &mut R,
+    ) -> Result<VerificationShare<G>, Error> {
+        let statement = DleqStatement::new(params, &self.share.value);
+        let proof = sigma::prove::<Dleq<G>, R>(
+            &verification_share_context(self.share.index),
+            &statement,
+            &self.share.value,
+            rng,
+        )?;
+        Ok(VerificationShare {
+            index: self.share.index,
+            y1: statement.y1,
+            y2: statement.y2,
+            proof,
+        })
+    }
+
+    /// First round: commits to fresh nonces `d_i` and `e_i`.
+    ///
+    /// # Returns
+    /// The commitment to broadcast and the nonces to keep for [`ThresholdSigner::respond`].
+    pub fn commit<R: RngCore + CryptoRng>(
+        &self,
+        params: &GroupParams<G>,
+        rng: &mut R,
+    ) -> (NonceCommitment<G>, SigningNonces<G>) {
+        let hiding = Nonce::random(rng);
+        let binding = Nonce::random(rng);
+        let commitment = NonceCommitment {
+            index: self.share.index,
+            hiding: (
+                params.g * hiding.expose_secret(),
+                params.h * hiding.expose_secret(),
+            ),
+            binding: (
+                params.g * binding.expose_secret(),
+                params.h * binding.expose_secret(),
+            ),
+        };
+        let nonces = SigningNonces {
+            commitment,
+            hiding,
+            binding,
+        };
+        (commitment, nonces)
+    }
+
+    /// Second round: answers the challenge `c` for the given round.
+    ///
+    /// # Arguments
+    /// * `nonces` - The nonces of this round's [`ThresholdSigner::commit`], consumed here.
+    /// * `context` - The context the round is bound to, the same for all signers.
+    /// * `commitments` - The first-round messages of all signers, including this one.
+    /// * `c` - The challenge of the aggregated commitment.
+    ///
+    /// # Returns
+    /// The partial response, or [`Error::InvalidSharingParams`] if this signer's
+    /// commitment is missing or altered, or an index is repeated.
+    pub fn respond(
+        &self,
+        nonces: SigningNonces<G>,
+        context: &[u8],
+        commitments: &[NonceCommitment<G>],
+        c: &G::Scalar,
+    ) -> Result<PartialResponse<G::Scalar>, Error> {
+        if !commitments.contains(&nonces.commitment) {
+            return Err(Error::InvalidSharingParams(format!(
+                "commitment of signer {} is missing or altered",
+                self.share.index
+            )));
+        }
+        let signers = signer_indices(commitments);
+        let lambda = lagrange_coefficient::<G::Scalar>(self.share.index, &signers)?;
+        let rho = binding_factor(context, commitments, self.share.index);
+        Ok(PartialResponse {
+            index: self.share.index,
+            s: nonces.hiding.consume()
+                + rho * nonces.binding.consume()
+                + *c * lambda * self.share.value.expose_secret(),
+        })
+    }
+}
+
+/// Collects the messages of the signers and combines them into a DLEQ transcript.
+///
+/// The coordinator holds no secret. It knows the Feldman commitments of the
+/// sharing and the verification shares of the signers, so it can check every
+/// partial response on its own and name the signers that cheated.
+#[derive(Clone, Debug)]
+pub struct ThresholdCoordinator<G: CurveBackend> {
+    params: GroupParams<G>,
+    threshold: usize,
+    y1: G,
+    y2: G,
+    shares: Vec<VerificationShare<G>>,
+}
+
+impl<G: CurveBackend> ThresholdCoordinator<G> {
+    /// Creates a coordinator after checking every verification share.
+    ///
+    /// # Returns
+    /// The coordinator, [`Error::InvalidProof`] if a verification share does not match
+    /// the commitments or its proof fails, or [`Error::InvalidSharingParams`] if there
+    /// are fewer than `t` shares to derive `y2 = h^x` from.
+    pub fn new(
+        params: &GroupParams<G>,
+        commitments: &FeldmanCommitments<G>,
+        shares: Vec<VerificationShare<G>>,
+    ) -> Result<Self, Error> {
+        for share in &shares {
+            let statement = DleqStatement {
+                g: params.g,
+                h: params.h,
+                y1: share.y1,
+                y2: share.y2,
+            };
+            let valid = share.index != 0
+                && share.y1 == commitments.verification_share(share.index)
+                && sigma::verify(
+                    &verification_share_context(share.index),
+                    &statement,
+                    &share.proof,
+                )?;
+            if !valid {
+                return Err(Error::InvalidProof);
+            }
+        }
+
+        let threshold = commitments.threshold();
+        if shares.len() < threshold {
+            return Err(Error::InvalidSharingParams(format!(
+                "{} verification shares for threshold {}",
+                shares.len(),
+                threshold
+            )));
+        }
+        let indices: Vec<_> = shares[..threshold].iter().map(|s| s.index).collect();
+        let mut y2 = G::identity();
+        for share in &shares[..threshold] {
+            y2 += share.y2 * lagrange_coefficient::<G::Scalar>(share.index, &indices)?;
+        }
+
+        Ok(Self {
+            params: *params,
+            threshold,
+            y1: commitments.public_key(),
+            y2,
+            shares,
+        })
+    }
+
+    /// The public values `(y1, y2) = (g^x, h^x)` of the shared secret.
+    pub fn public_key(&self) -> (G, G) {
+        (self.y1, self.y2)
+    }
+
+    /// Combines the first-round messages into the commitment parameters `(y1, y2, r1, r2)`,
+    /// with `r1 = sum(g^d_i * g^(e_i * rho_i))` and `r2` the same on `h`.
+    ///
+    /// These are the commitment parameters the craft server expects from a client.
+    ///
+    /// # Returns
+    /// The commitment parameters, or [`Error::InvalidSharingParams`] if fewer than
+    /// `t` signers took part, or a signer is repeated or has no verification share.
+    pub fn aggregate(
+        &self,
+        context: &[u8],
+        commitments: &[NonceCommitment<G>],
+    ) -> Result<(G, G, G, G), Error> {
+        self.check_signers(&signer_indices(commitments))?;
+        let (r1, r2) = commitments
+            .iter()
+            .map(|commitment| bound_commitment(context, commitments, commitment))
+            .fold((G::identity(), G::identity()), |(r1, r2), (a, b)| {
+                (r1 + a, r2 + b)
+            });
+        Ok((self.y1, self.y2, r1, r2))
+    }
+
+    /// Checks `g^s_i == g^d_i * g^(e_i * rho_i) * (g^x_i)^(c * lambda_i)` and the same
+    /// equation on `h`.
+    ///
+    /// # Returns
+    /// Whether the partial response is valid, or [`Error::InvalidSharingParams`] if
+    /// an index in `commitments` is repeated.
+    pub fn verify_partial(
+        &self,
+        context: &[u8],
+        commitments: &[NonceCommitment<G>],
+        c: &G::Scalar,
+        partial: &PartialResponse<G::Scalar>,
+    ) -> Result<bool, Error> {
+        let Some(share) = self.share(partial.index) else {
+            return Ok(false);
+        };
+        let Some(commitment) = commitments.iter().find(|c| c.index == partial.index) else {
+            return Ok(false);
+        };
+        let signers = signer_indices(commitments);
+        let e = *c * lagrange_coefficient::<G::Scalar>(partial.index, &signers)?;
+        let (r1, r2) = bound_commitment(context, commitments, commitment);
+        Ok(self.params.g * partial.s == r1 + share.y1 * e
+            && self.params.h * partial.s == r2 + share.y2 * e)
+    }
+
+    /// Combines the partial responses into the response `s` of the aggregated transcript.
+    ///
+    /// # Arguments
+    /// * `context` - The context passed to [`ThresholdCoordinator::aggregate`].
+    /// * `commitments` - The first-round messages passed to [`ThresholdCoordinator::aggregate`].
+    /// * `c` - The challenge of the aggregated commitment.
+    /// * `partials` - The second-round messages, in any order.
+    ///
+    /// # Returns
+    /// The response, or the indices of the signers whose partial response is missing
+    /// or invalid. Fails with [`Error::InvalidSharingParams`], without blaming anyone,
+    /// if the set of signers is one [`ThresholdCoordinator::aggregate`] rejects.
+    pub fn combine(
+        &self,
+        context: &[u8],
+        commitments: &[NonceCommitment<G>],
+        c: &G::Scalar,
+        partials: &[PartialResponse<G::Scalar>],
+    ) -> Result<Result<G::Scalar, Vec<u32>>, Error> {
+        self.check_signers(&signer_indices(commitments))?;
+        let mut s = G::Scalar::ZERO;
+        let mut cheaters = Vec::new();
+        for commitment in commitments {
+            let partial = partials.iter().find(|p| p.index == commitment.index);
+            match partial {
+                Some(partial) if self.verify_partial(context, commitments, c, partial)? => {
+                    s += partial.s;
+                }
+                _ => cheaters.push(commitment.index),
+            }
+        }
+        if cheaters.is_empty() {
+            Ok(Ok(s))
+        } else {
+            Ok(Err(cheaters))
+        }
+    }
+
+    fn share(&self, index: u32) -> Option<&VerificationShare<G>> {
+        self.shares.iter().find(|share| share.index == index)
+    }
+
+    fn check_signers(&self, signers: &[u32]) -> Result<(), Error> {
+        if signers.len() < self.threshold {
+            return Err(Error::InvalidSharingParams(format!(
+                "{} signers for threshold {}",
+                signers.len(),
+                self.threshold
+            )));
+        }
+        for (k, index) in signers.iter().enumerate() {
+            if signers[..k].contains(index) || self.share(*index).is_none() {
+                return Err(Error::InvalidSharingParams(format!(
+                    "unknown or repeated signer {}",
+                    index
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The indices of the signers of a round, in the order of their commitments.
+fn signer_indices<G>(commitments: &[NonceCommitment<G>]) -> Vec<u32> {
+    commitments.iter().map(|c| c.index).collect()
+}
+
+/// Computes the binding factor `rho_i = H(i, context, commitments)` of signer `index`.
+fn binding_factor<G: CurveBackend>(
+    context: &[u8],
+    commitments: &[NonceCommitment<G>],
+    index: u32,
+) -> G::Scalar {
+    let mut message = index.to_be_bytes().to_vec();
+    message.extend_from_slice(&(context.len() as u64).to_le_bytes());
+    message.extend_from_slice(context);
+    for commitment in commitments {
+        message.extend_from_slice(&commitment.index.to_be_bytes());
+        for point in [
+            commitment.hiding.0,
+            commitment.hiding.1,
+            commitment.binding.0,
+            commitment.binding.1,
+        ] {
+            message.extend(G::to(&point));
+        }
+    }
+    G::hash_to_scalar(BINDING_FACTOR_DOMAIN, &message)
+}
+
+/// The commitments `(g^d_i * g^(e_i * rho_i), h^d_i * h^(e_i * rho_i))` of a signer's
+/// nonce in this round.
+fn bound_commitment<G: CurveBackend>(
+    context: &[u8],
+    commitments: &[NonceCommitment<G>],
+    commitment: &NonceCommitment<G>,
+) -> (G, G) {
+    let rho = binding_factor(context, commitments, commitment.index);
+    (
+        commitment.hiding.0 + commitment.binding.0 * rho,
+        commitment.hiding.1 + commitment.binding.1 * rho,
+    )
+}
+
+/// Binds the proof of a verification share to its index.
+fn verification_share_context(index: u32) -> Vec<u8> {
+    [VERIFICATION_SHARE_CONTEXT, &index.to_be_bytes()].concat()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::constants::{PALLAS_GROUP_PARAMS, VESTA_GROUP_PARAMS};
+    use crate::protocol::elliptic_curves::dleq::DleqEllipticCurve;
+    use crate::protocol::fiat_shamir::{dleq_challenge, DleqProof, NonInteractiveProtocol};
+    use crate::protocol::vss::split;
+    use crate::protocol::Protocol;
+    use crate::secret::SecretScalar;
+    use pasta_curves::{pallas, vesta};
+    use rand_core::OsRng;
+
+    fn setup<G: CurveBackend>(
+        params: &GroupParams<G>,
+    ) -> (
+        SecretScalar<G::Scalar>,
+        Vec<ThresholdSigner<G>>,
+        ThresholdCoordinator<G>,
+    ) {
+        let x = SecretScalar::random(&mut OsRng);
+        let (shares, commitments) = split(params, &x, 3, 5, &mut OsRng).unwrap();
+        let signers: Vec<_> = shares.into_iter().map(ThresholdSigner::new).collect();
+        let verification_shares = signers
+            .iter()
+            .map(|s| s.verification_share(params, &mut OsRng).unwrap())
+            .collect();
+        let coordinator = ThresholdCoordinator::new(params, &commitments, verification_shares);
+        (x, signers, coordinator.unwrap())
+    }
+
+    /// The messages of one run of both rounds.
+    struct Round<G: CurveBackend> {
+        cp: (G, G, G, G),
+        c: G::Scalar,
+        commitments: Vec<NonceCommitment<G>>,
+        partials: Vec<PartialResponse<G::Scalar>>,
+    }
+
+    /// Runs both rounds with the given signers, in the context `"login"`.
+    fn run_round<G: CurveBackend>(
+        params: &GroupParams<G>,
+        coordinator: &ThresholdCoordinator<G>,
+        signers: &[&ThresholdSigner<G>],
+        challenge: impl Fn(&(G, G, G, G)) -> G::Scalar,
+    ) -> Round<G> {
+        let (commitments, nonces): (Vec<_>, Vec<_>) = signers
+            .iter()
+            .map(|signer| signer.commit(params, &mut OsRng))
+            .unzip();
+        let cp = coordinator.aggregate(b"login", &commitments).unwrap();
+        let c = challenge(&cp);
+        let partials = signers
+            .iter()
+            .zip(nonces)
+            .map(|(signer, nonces)| signer.respond(nonces, b"login", &commitments, &c).unwrap())
+            .collect();
+        Round {
+            cp,
+            c,
+            commitments,
+            partials,
+        }
+    }
+
+    fn threshold_dleq<G: CurveBackend>(params: &GroupParams<G>) {
+        let (x, signers, coordinator) = setup(params);
+        let statement = DleqStatement::new(params, &x);
+        assert_eq!(coordinator.public_key(), (statement.y1, statement.y2));
+
+        // Any three of the five signers, interactively with a server challenge.
+        let round = [&signers[4], &signers[0], &signers[2]];
+        let Round {
+            cp,
+            c,
+            commitments,
+            partials,
+        } = run_round(params, &coordinator, &round, |_| {
+            DleqEllipticCurve::<G>::challenge(params)
+        });
+        let s = coordinator
+            .combine(b"login", &commitments, &c, &partials)
+            .unwrap()
+            .unwrap();
+        assert_eq!(DleqEllipticCurve::verify(params, &s, &c, &cp), Ok(true));
+
+        // Non-interactively, with the Fiat–Shamir challenge of `DleqProof`.
+        let round: Vec<_> = signers[1..4].iter().collect();
+        let Round {
+            cp,
+            c,
+            commitments,
+            partials,
+        } = run_round(params, &coordinator, &round, |cp| {
+            dleq_challenge(params, cp)
+        });
+        let s = coordinator
+            .combine(b"login", &commitments, &c, &partials)
+            .unwrap()
+            .unwrap();
+        let (y1, y2, r1, r2) = cp;
+        let proof = DleqProof { y1, y2, r1, r2, s };
+        assert_eq!(DleqEllipticCurve::verify_proof(params, &proof), Ok(true));
+    }
+
+    fn binds_commitments<G: CurveBackend>(params: &GroupParams<G>) {
+        let (_, signers, coordinator) = setup(params);
+        let (commitments, mut nonces): (Vec<_>, Vec<_>) = signers[..3]
+            .iter()
+            .map(|signer| signer.commit(params, &mut OsRng))
+            .unzip();
+
+        // The same commitment of signer 1 is bound differently in another context or
+        // next to other commitments, so its nonce cannot be steered across rounds.
+        let cp = coordinator.aggregate(b"login", &commitments).unwrap();
+        assert_ne!(coordinator.aggregate(b"other", &commitments).unwrap(), cp);
+        let (other, _) = signers[3].commit(params, &mut OsRng);
+        let mixed = [commitments[0], commitments[1], other];
+        let (_, _, r1, _) = coordinator.aggregate(b"login", &mixed).unwrap();
+        assert_ne!(r1, cp.2);
+
+        // Nor is it plainly summed.
+        let sum: G = commitments.iter().map(|c| c.hiding.0 + c.binding.0).sum();
+        assert_ne!(cp.2, sum);
+
+        // A signer refuses to answer for an altered copy of its own commitment.
+        let c = G::Scalar::random(OsRng);
+        let mut altered = commitments.clone();
+        altered[0].binding = altered[1].binding;
+        assert!(matches!(
+            signers[0].respond(nonces.remove(0), b"login", &altered, &c),
+            Err(Error::InvalidSharingParams(_))
+        ));
+        assert!(matches!(
+            signers[1].respond(nonces.remove(0), b"login", &commitments[..1], &c),
+            Err(Error::InvalidSharingParams(_))
+        ));
+    }
+
+    fn identifies_cheaters<G: CurveBackend>(params: &GroupParams<G>) {
+        let (_, signers, coordinator) = setup(params);
+        let round: Vec<_> = signers[..4].iter().collect();
+        let c = G::Scalar::random(OsRng);
+        let Round {
+            commitments,
+            mut partials,
+            ..
+        } = run_round(params, &coordinator, &round, |_| c);
+        partials[1].s += G::Scalar::ONE;
+        partials.remove(3);
+        assert_eq!(
+            coordinator.combine(b"login", &commitments, &c, &partials),
+            Ok(Err(vec![2, 4]))
+        );
+        // Partials for another context fail for everyone.
+        partials.truncate(1);
+        assert_eq!(
+            coordinator.combine(b"other", &commitments[..3], &c, &partials),
+            Ok(Err(vec![1, 2, 3]))
+        );
+
+        // A malformed set of signers is an error, not a list of cheaters.
+        assert!(matches!(
+            coordinator.aggregate(b"login", &commitments[..2]),
+            Err(Error::InvalidSharingParams(_))
+        ));
+        assert!(matches!(
+            coordinator.combine(b"login", &commitments[..2], &c, &partials),
+            Err(Error::InvalidSharingParams(_))
+        ));
+        let repeated = [commitments[0], commitments[1], commitments[1]];
+        assert!(matches!(
+            coordinator.combine(b"login", &repeated, &c, &partials),
+            Err(Error::InvalidSharingParams(_))
+        ));
+    }
+
+    fn rejects_bad_verification_shares<G: CurveBackend>(params: &GroupParams<G>) {
+        let x = SecretScalar::random(&mut OsRng);
+        let (shares, commitments) = split(params, &x, 2, 3, &mut OsRng).unwrap();
+        let signers: Vec<_> = shares.into_iter().map(ThresholdSigner::new).collect();
+        let mut verification_shares: Vec<_> = signers
+            .iter()
+            .map(|s| s.verification_share(params, &mut OsRng).unwrap())
+            .collect();
+        verification_shares[0].index = 3;
+        assert_eq!(
+            ThresholdCoordinator::new(params, &commitments, verification_shares).unwrap_err(),
+            Error::InvalidProof
+        );
+    }
+
+    #[test]
+    fn pallas_threshold_dleq() {
+        threshold_dleq::<pallas::Point>(&PALLAS_GROUP_PARAMS);
+        binds_commitments::<pallas::Point>(&PALLAS_GROUP_PARAMS);
+        identifies_cheaters::<pallas::Point>(&PALLAS_GROUP_PARAMS);
+        rejects_bad_verification_shares::<pallas::Point>(&PALLAS_GROUP_PARAMS);
+    }
+
+    #[test]
+    fn vesta_threshold_dleq() {
+        threshold_dleq::<vesta::Point>(&VESTA_GROUP_PARAMS);
+        binds_commitments::<vesta::Point>(&VESTA_GROUP_PARAMS);
+        identifies_cheaters::<vesta::Point>(&VESTA_GROUP_PARAMS);
+        rejects_bad_verification_shares::<vesta::Point>(&VESTA_GROUP_PARAMS);
+    }
+}
@@ -0,0 +1,343 @@
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::msm::multiscalar_mul;
+use crate::protocol::sigma::dleq::{Dleq, DleqStatement};
+use crate::protocol::sigma::{self, SigmaProof};
+use crate::protocol::transcript::Transcript;
+use crate::protocol::GroupParams;
+use crate::secret::SecretScalar;
+use crate::Error;
+use pasta_curves::group::ff::Field;
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha512};
+use std::fmt;
+use zeroize::Zeroizing;
+
+/// The length of a PRF output, that of a SHA-512 digest.
+pub const OUTPUT_LEN: usize = 64;
+
+/// Returns the RFC 9497 context string `"OPRFV1-" || mode || "-" || identifier` of a curve,
+/// with the VOPRF mode `0x01` and identifier `ec_snark-<curve>`.
+///
+/// The steps follow RFC 9497, but the hash-to-group, the DLEQ proof and the
+/// composite weights are those of this crate, so outputs are not interoperable
+/// with the ciphersuites registered in the RFC.
+pub fn context_string<G: CurveBackend>() -> String {
+    format!("OPRFV1-\u{1}-ec_snark-{}", G::CURVE)
+}
+
+/// Hashes a PRF input to the group.
+fn hash_input<G: CurveBackend>(input: &[u8]) -> G {
+    G::hash_to_group(&format!("HashToGroup-{}", context_string::<G>()), input)
+}
+
+/// The two-byte length prefix of a PRF input, or [`Error::InvalidLength`] if the
+/// input is longer than RFC 9497 allows.
+fn input_len(input: &[u8]) -> Result<u16, Error> {
+    u16::try_from(input.len()).map_err(|_| Error::InvalidLength {
+        expected: u16::MAX as usize,
+        actual: input.len(),
+    })
+}
+
+/// Computes the PRF output `SHA-512(len(input) || input || len(N) || N || "Finalize")`.
+fn finalize_output<G: CurveBackend>(input: &[u8], n: &G) -> Result<[u8; OUTPUT_LEN], Error> {
+    let element = G::to(n);
+    let mut hasher = Sha512::new();
+    hasher.update(input_len(input)?.to_be_bytes());
+    hasher.update(input);
+    hasher.update((element.len() as u16).to_be_bytes());
+    hasher.update(&element);
+    hasher.update(b"Finalize");
+    Ok(hasher.finalize().into())
+}
+
+/// Computes the composite elements `M = sum(d_i * C_i)` and `Z = sum(d_i * D_i)`.
+///
+/// The weights `d_i` are drawn from a transcript over the public key and all pairs,
+/// so a single DLEQ proof for `(M, Z)` covers every pair of a batch.
+fn composites<G: CurveBackend>(public_key: &G, blinded: &[G], evaluated: &[G]) -> (G, G) {
+    let mut transcript = Transcript::new(context_string::<G>().as_bytes());
+    transcript.append_element(b"public-key", public_key);
+    transcript.append_message(b"count", &(blinded.len() as u64).to_le_bytes());
+    for (c, d) in blinded.iter().zip(evaluated) {
+        transcript.append_element(b"blinded", c);
+        transcript.append_element(b"evaluated", d);
+    }
+    let weights: Vec<_> = blinded
+        .iter()
+        .map(|_| transcript.challenge_scalar::<G>(b"composite"))
+        .collect();
+    (
+        multiscalar_mul(&weights, blinded),
+        multiscalar_mul(&weights, evaluated),
+    )
+}
+
+/// The statement `log_g(Y) == log_M(Z)` proven by the server.
+fn proof_statement<G: CurveBackend>(
+    params: &GroupParams<G>,
+    public_key: &G,
+    blinded: &[G],
+    evaluated: &[G],
+) -> DleqStatement<G> {
+    let (m, z) = composites(public_key, blinded, evaluated);
+    DleqStatement {
+        g: params.g,
+        h: m,
+        y1: *public_key,
+        y2: z,
+    }
+}
+
+/// The server of the VOPRF, holding the PRF key `k` and its public key `Y = g^k`.
+#[derive(Debug)]
+pub struct VoprfServer<G: CurveBackend> {
+    key: SecretScalar<G::Scalar>,
+    public_key: G,
+}
+
+/// The server's answer to a batch of blinded elements.
+#[derive(Clone, Debug)]
+pub struct BlindEvaluation<G: CurveBackend> {
+    /// The evaluated elements `D_i = C_i^k`, in the order of the request.
+    pub evaluated: Vec<G>,
+    /// One DLEQ proof for the whole batch.
+    pub proof: SigmaProof<Dleq<G>>,
+}
+
+/// The client's state between [`blind`] and [`finalize`].
+///
+/// The input is typically a password, so it is zeroized on drop and redacted in `Debug`.
+pub struct Blind<G: CurveBackend> {
+    input: Zeroizing<Vec<u8>>,
+    blind: SecretScalar<G::Scalar>,
+}
+
+impl<G: CurveBackend> fmt::Debug for Blind<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Blind(<redacted>)")
+    }
+}
+
+impl<G: CurveBackend> VoprfServer<G> {
+    /// Creates a server with a random key.
+    pub fn random<R: RngCore + CryptoRng>(params: &GroupParams<G>, rng: &mut R) -> Self {
+        Self::new(params, SecretScalar::random(rng))
+    }
+
+    /// Creates a server with the given key.
+    pub fn new(params: &GroupParams<G>, key: SecretScalar<G::Scalar>) -> Self {
+        let public_key = params.g * key.expose_secret();
+        Self { key, public_key }
+    }
+
+    /// The public key `Y = g^k` clients verify evaluations against.
+    pub fn public_key(&self) -> G {
+        self.public_key
+    }
+
+    /// Evaluates the blinded elements and proves that all of them used the key of `Y`.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters of the curve.
+    /// * `blinded` - The blinded elements of one or more [`blind`] calls.
+    /// * `rng` - The source of the prover's randomness.
+    ///
+    /// # Returns
+    /// The evaluated elements and the batch proof, or [`Error::IdentityPoint`] if a
+    /// blinded element is the identity.
+    pub fn blind_evaluate<R: RngCore + CryptoRng>(
+        &self,
+        params: &GroupParams<G>,
+        blinded: &[G],
+        rng: &mut R,
+    ) -> Result<BlindEvaluation<G>, Error> {
+        if blinded.iter().any(|c| bool::from(c.is_identity())) {
+            return Err(Error::IdentityPoint);
+        }
+        let evaluated: Vec<_> = blinded
+            .iter()
+            .map(|c| *c * self.key.expose_secret())
+            .collect();
+        let statement = proof_statement(params, &self.public_key, blinded, &evaluated);
+        let proof = sigma::prove::<Dleq<G>, R>(
+            context_string::<G>().as_bytes(),
+            &statement,
+            &self.key,
+            rng,
+        )?;
+        Ok(BlindEvaluation { evaluated, proof })
+    }
+
+    /// Computes the PRF output for an input directly, without blinding.
+    ///
+    /// It equals the output of [`finalize`] for the same input, so the server can
+    /// recompute outputs it handed out. Inputs longer than `u16::MAX` bytes are
+    /// rejected with [`Error::InvalidLength`].
+    pub fn evaluate(&self, input: &[u8]) -> Result<[u8; OUTPUT_LEN], Error> {
+        finalize_output(input, &(hash_input::<G>(input) * self.key.expose_secret()))
+    }
+}
+
+/// Blinds a PRF input with a random scalar `r`.
+///
+/// # Returns
+/// The client's state and the blinded element `C = H(input)^r` to send to the server,
+/// or [`Error::InvalidLength`] if the input is longer than `u16::MAX` bytes.
+pub fn blind<G: CurveBackend, R: RngCore + CryptoRng>(
+    input: &[u8],
+    rng: &mut R,
+) -> Result<(Blind<G>, G), Error> {
+    input_len(input)?;
+    let blind = SecretScalar::<G::Scalar>::random(rng);
+    let blinded = hash_input::<G>(input) * blind.expose_secret();
+    if bool::from(blinded.is_identity()) {
+        return Err(Error::IdentityPoint);
+    }
+    let state = Blind {
+        input: Zeroizing::new(input.to_vec()),
+        blind,
+    };
+    Ok((state, blinded))
+}
+
+/// Verifies the server's batch proof and unblinds the PRF outputs.
+///
+/// # Arguments
+/// * `params` - Group parameters of the curve.
+/// * `public_key` - The server's public key `Y`.
+/// * `blinds` - The states of the [`blind`] calls, in the order of `blinded`.
+/// * `blinded` - The blinded elements sent to the server.
+/// * `evaluation` - The server's answer.
+///
+/// # Returns
+/// One output per input, or [`Error::InvalidProof`] if the answer does not match the
+/// request or the proof does not verify.
+pub fn finalize<G: CurveBackend>(
+    params: &GroupParams<G>,
+    public_key: &G,
+    blinds: &[Blind<G>],
+    blinded: &[G],
+    evaluation: &BlindEvaluation<G>,
+) -> Result<Vec<[u8; OUTPUT_LEN]>, Error> {
+    if blinds.len() != blinded.len() || evaluation.evaluated.len() != blinded.len() {
+        return Err(Error::InvalidProof);
+    }
+    let statement = proof_statement(params, public_key, blinded, &evaluation.evaluated);
+    let valid = sigma::verify(
+        context_string::<G>().as_bytes(),
+        &statement,
+        &evaluation.proof,
+    )?;
+    if !valid {
+        return Err(Error::InvalidProof);
+    }
+
+    blinds
+        .iter()
+        .zip(&evaluation.evaluated)
+        .map(|(state, d)| {
+            let inverse = Option::<G::Scalar>::from(state.blind.expose_secret().invert())
+                .ok_or(Error::InvalidWitness)?;
+            finalize_output(&state.input, &(*d * inverse))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::constants::{
+        BLS12_381_GROUP_PARAMS, PALLAS_GROUP_PARAMS, RISTRETTO_GROUP_PARAMS, VESTA_GROUP_PARAMS,
+    };
+    use bls12_381::G1Projective;
+    use curve25519_dalek::ristretto::RistrettoPoint;
+    use pasta_curves::{pallas, vesta};
+    use rand_core::OsRng;
+
+    fn voprf<G: CurveBackend>(params: &GroupParams<G>) {
+        let server = VoprfServer::<G>::random(params, &mut OsRng);
+        let inputs: [&[u8]; 3] = [b"hunter2", b"correct horse", b""];
+        let (blinds, blinded): (Vec<_>, Vec<_>) = inputs
+            .iter()
+            .map(|input| blind::<G, _>(input, &mut OsRng).unwrap())
+            .unzip();
+
+        let evaluation = server.blind_evaluate(params, &blinded, &mut OsRng).unwrap();
+        let outputs =
+            finalize(params, &server.public_key(), &blinds, &blinded, &evaluation).unwrap();
+        for (input, output) in inputs.iter().zip(&outputs) {
+            assert_eq!(*output, server.evaluate(input).unwrap());
+        }
+        assert_ne!(outputs[0], outputs[1]);
+
+        // The output does not depend on the blind.
+        let (state, element) = blind::<G, _>(inputs[0], &mut OsRng).unwrap();
+        let single = server
+            .blind_evaluate(params, &[element], &mut OsRng)
+            .unwrap();
+        let output = finalize(params, &server.public_key(), &[state], &[element], &single);
+        assert_eq!(output.unwrap()[0], outputs[0]);
+
+        let other = VoprfServer::<G>::random(params, &mut OsRng);
+        assert_eq!(
+            finalize(params, &other.public_key(), &blinds, &blinded, &evaluation).unwrap_err(),
+            Error::InvalidProof
+        );
+        let mut tampered = evaluation.clone();
+        tampered.evaluated.swap(0, 1);
+        assert_eq!(
+            finalize(params, &server.public_key(), &blinds, &blinded, &tampered).unwrap_err(),
+            Error::InvalidProof
+        );
+        assert_eq!(
+            finalize(
+                params,
+                &server.public_key(),
+                &blinds[1..],
+                &blinded[1..],
+                &evaluation
+            )
+            .unwrap_err(),
+            Error::InvalidProof
+        );
+        assert_eq!(
+            server
+                .blind_evaluate(params, &[G::identity()], &mut OsRng)
+                .unwrap_err(),
+            Error::IdentityPoint
+        );
+        assert_eq!(format!("{:?}", blinds[0]), "Blind(<redacted>)");
+
+        // Inputs must fit the two-byte length prefix of the output hash.
+        let longest = vec![0; u16::MAX as usize];
+        assert!(blind::<G, _>(&longest, &mut OsRng).is_ok());
+        let too_long = vec![0; u16::MAX as usize + 1];
+        let expected = Error::InvalidLength {
+            expected: u16::MAX as usize,
+            actual: too_long.len(),
+        };
+        assert_eq!(blind::<G, _>(&too_long, &mut OsRng).unwrap_err(), expected);
+        assert_eq!(server.evaluate(&too_long).unwrap_err(), expected);
+    }
+
+    #[test]
+    fn pallas_voprf() {
+        voprf::<pallas::Point>(&PALLAS_GROUP_PARAMS);
+    }
+
+    #[test]
+    fn vesta_voprf() {
+        voprf::<vesta::Point>(&VESTA_GROUP_PARAMS);
+    }
+
+    #[test]
+    fn ristretto_voprf() {
+        voprf::<RistrettoPoint>(&RISTRETTO_GROUP_PARAMS);
+    }
+
+    #[test]
+    fn bls12381_voprf() {
+        voprf::<G1Projective>(&BLS12_381_GROUP_PARAMS);
+    }
+}
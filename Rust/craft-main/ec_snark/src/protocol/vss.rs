@@ -0,0 +1,329 @@
+use crate::common::{self, FromBytes, IntoBytes};
+use crate::protocol::elliptic_curves::CurveBackend;
+use crate::protocol::msm::multiscalar_mul;
+use crate::protocol::GroupParams;
+use crate::secret::SecretScalar;
+use crate::Error;
+use pasta_curves::group::ff::{Field, PrimeField};
+use rand_core::{CryptoRng, RngCore};
+
+/// A Shamir share `(i, f(i))` of a secret scalar `x = f(0)`.
+///
+/// Indices start at 1; the share value is zeroized when dropped.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Share<F: Field> {
+    /// The evaluation point `i`.
+    pub index: u32,
+    /// The evaluation `f(i)`.
+    pub value: SecretScalar<F>,
+}
+
+/// The shares of one secret, in index order.
+pub type Shares<F> = Vec<Share<F>>;
+
+/// Feldman commitments `C_j = g^(a_j)` to the coefficients of the sharing polynomial.
+///
+/// `C_0 = g^x` is the public key of the shared secret; for the password scalar it
+/// equals the `y1` the user registered with. The number of commitments is the threshold.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeldmanCommitments<G> {
+    points: Vec<G>,
+}
+
+/// Splits `secret` into `n` shares, any `t` of which recover it.
+///
+/// # Arguments
+/// * `params` - Group parameters of the curve; the coefficients are committed on `g`.
+/// * `secret` - The scalar to be shared.
+/// * `t` - The threshold, between 1 and `n`.
+/// * `n` - The number of shares.
+/// * `rng` - The source of the polynomial coefficients.
+///
+/// # Returns
+/// The shares with indices `1..=n` and the commitments to publish, or
+/// [`Error::InvalidSharingParams`] if `t` is not in `1..=min(n, u16::MAX)`.
+pub fn split<G: CurveBackend, R: RngCore + CryptoRng>(
+    params: &GroupParams<G>,
+    secret: &SecretScalar<G::Scalar>,
+    t: usize,
+    n: u32,
+    rng: &mut R,
+) -> Result<(Shares<G::Scalar>, FeldmanCommitments<G>), Error> {
+    if t == 0 || t > n as usize || t > u16::MAX as usize {
+        return Err(Error::InvalidSharingParams(format!(
+            "threshold {} of {} shares",
+            t, n
+        )));
+    }
+    let coefficients: Vec<_> = std::iter::once(SecretScalar::new(*secret.expose_secret()))
+        .chain((1..t).map(|_| SecretScalar::random(rng)))
+        .collect();
+    let commitments = FeldmanCommitments {
+        points: coefficients
+            .iter()
+            .map(|a| params.g * a.expose_secret())
+            .collect(),
+    };
+    let shares = (1..=n)
+        .map(|index| {
+            // Horner's rule from the highest coefficient down.
+            let i = G::Scalar::from(index as u64);
+            let value = coefficients
+                .iter()
+                .rev()
+                .fold(G::Scalar::ZERO, |acc, a| acc * i + a.expose_secret());
+            Share {
+                index,
+                value: SecretScalar::new(value),
+            }
+        })
+        .collect();
+    Ok((shares, commitments))
+}
+
+/// Recovers the secret from the first `t` of the given shares by Lagrange interpolation at 0.
+///
+/// The result is only correct if those shares are; check them with [`Share::verify`] first.
+///
+/// # Returns
+/// The secret, or [`Error::InvalidSharingParams`] if there are fewer than `t` shares
+/// or an index is zero or repeated.
+pub fn recover<F: PrimeField>(shares: &[Share<F>], t: usize) -> Result<SecretScalar<F>, Error> {
+    if t == 0 || shares.len() < t {
+        return Err(Error::InvalidSharingParams(format!(
+            "{} shares for threshold {}",
+            shares.len(),
+            t
+        )));
+    }
+    let indices: Vec<_> = shares[..t].iter().map(|share| share.index).collect();
+    let mut secret = F::ZERO;
+    for share in &shares[..t] {
+        secret += *share.value.expose_secret() * lagrange_coefficient::<F>(share.index, &indices)?;
+    }
+    Ok(SecretScalar::new(secret))
+}
+
+/// Computes the Lagrange coefficient `prod_{j != i} j / (j - i)` of share `index`
+/// for interpolating at 0 from the shares with the given indices.
+///
+/// # Returns
+/// The coefficient, or [`Error::InvalidSharingParams`] if `index` is not among
+/// `indices`, or an index is zero or repeated.
+pub fn lagrange_coefficient<F: PrimeField>(index: u32, indices: &[u32]) -> Result<F, Error> {
+    for (k, j) in indices.iter().enumerate() {
+        if *j == 0 || indices[..k].contains(j) {
+            return Err(Error::InvalidSharingParams(format!(
+                "invalid or repeated share index {}",
+                j
+            )));
+        }
+    }
+    if !indices.contains(&index) {
+        return Err(Error::InvalidSharingParams(format!(
+            "share index {} is not interpolated",
+            index
+        )));
+    }
+    let x_i = F::from(index as u64);
+    let (numerator, denominator) = indices
+        .iter()
+        .filter(|j| **j != index)
+        .map(|j| F::from(*j as u64))
+        .fold((F::ONE, F::ONE), |(num, den), x_j| {
+            (num * x_j, den * (x_j - x_i))
+        });
+    // The indices are distinct, so the denominator is not zero.
+    Ok(numerator * denominator.invert().unwrap())
+}
+
+impl<F: Field> Share<F> {
+    /// Checks the share against the published commitments: `g^f(i) == prod C_j^(i^j)`.
+    pub fn verify<G>(&self, params: &GroupParams<G>, commitments: &FeldmanCommitments<G>) -> bool
+    where
+        G: CurveBackend<Scalar = F>,
+    {
+        if self.index == 0 || commitments.points.is_empty() {
+            return false;
+        }
+        params.g * self.value.expose_secret() == commitments.verification_share(self.index)
+    }
+}
+
+impl<G: CurveBackend> FeldmanCommitments<G> {
+    /// The threshold `t` of the sharing.
+    pub fn threshold(&self) -> usize {
+        self.points.len()
+    }
+
+    /// The commitment `C_0 = g^x` to the shared secret.
+    pub fn public_key(&self) -> G {
+        self.points[0]
+    }
+
+    /// Computes `g^f(i) = prod C_j^(i^j)`, the public counterpart of share `i`.
+    pub fn verification_share(&self, index: u32) -> G {
+        let i = G::Scalar::from(index as u64);
+        let powers: Vec<_> = std::iter::successors(Some(G::Scalar::ONE), |p| Some(*p * i))
+            .take(self.points.len())
+            .collect();
+        multiscalar_mul(&powers, &self.points)
+    }
+
+    /// The commitments `C_0, ..., C_(t-1)`.
+    pub fn points(&self) -> &[G] {
+        &self.points
+    }
+}
+
+impl<F: Field + IntoBytes<F>> IntoBytes<Share<F>> for Share<F> {
+    fn to(t: &Share<F>) -> Vec<u8> {
+        common::encode_parts(&[
+            t.index.to_be_bytes().to_vec(),
+            F::to(t.value.expose_secret()),
+        ])
+    }
+}
+
+impl<F: Field + FromBytes<F>> FromBytes<Share<F>> for Share<F> {
+    fn from(bytes: &[u8]) -> Result<Share<F>, Error> {
+        let parts = common::decode_parts(bytes, 2)?;
+        let index: [u8; 4] = parts[0].try_into().map_err(|_| Error::InvalidLength {
+            expected: 4,
+            actual: parts[0].len(),
+        })?;
+        Ok(Share {
+            index: u32::from_be_bytes(index),
+            value: SecretScalar::new(F::from(parts[1])?),
+        })
+    }
+}
+
+impl<G: CurveBackend> IntoBytes<FeldmanCommitments<G>> for FeldmanCommitments<G> {
+    /// Encodes the threshold as a big-endian `u16`, followed by the commitments.
+    ///
+    /// [`split`] bounds the threshold to `u16::MAX`, so it always fits.
+    fn to(t: &FeldmanCommitments<G>) -> Vec<u8> {
+        let threshold = u16::try_from(t.points.len()).expect("Threshold bounded by split");
+        let mut parts = vec![threshold.to_be_bytes().to_vec()];
+        parts.extend(t.points.iter().map(G::to));
+        common::encode_parts(&parts)
+    }
+}
+
+impl<G: CurveBackend + FromBytes<G>> FromBytes<FeldmanCommitments<G>> for FeldmanCommitments<G> {
+    fn from(bytes: &[u8]) -> Result<FeldmanCommitments<G>, Error> {
+        let t = match bytes {
+            [0, 2, hi, lo, ..] => u16::from_be_bytes([*hi, *lo]) as usize,
+            _ => return Err(Error::NonCanonicalEncoding),
+        };
+        if t == 0 {
+            return Err(Error::NonCanonicalEncoding);
+        }
+        let parts = common::decode_parts(bytes, t + 1)?;
+        Ok(FeldmanCommitments {
+            points: parts[1..]
+                .iter()
+                .map(|part| <G as FromBytes<G>>::from(part))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::constants::{PALLAS_GROUP_PARAMS, VESTA_GROUP_PARAMS};
+    use pasta_curves::{pallas, vesta};
+    use rand_core::OsRng;
+
+    fn share_and_recover<G>(params: &GroupParams<G>)
+    where
+        G: CurveBackend + FromBytes<G>,
+        G::Scalar: IntoBytes<G::Scalar> + FromBytes<G::Scalar>,
+    {
+        let secret = SecretScalar::random(&mut OsRng);
+        let (mut shares, commitments) = split(params, &secret, 3, 5, &mut OsRng).unwrap();
+        assert_eq!(commitments.threshold(), 3);
+        assert_eq!(commitments.public_key(), params.g * secret.expose_secret());
+        assert!(shares
+            .iter()
+            .all(|share| share.verify(params, &commitments)));
+
+        assert_eq!(recover(&shares, 3).unwrap(), secret);
+        shares.reverse();
+        assert_eq!(recover(&shares[1..], 3).unwrap(), secret);
+        assert_ne!(recover(&shares[..2], 2).unwrap(), secret);
+        assert!(matches!(
+            recover(&shares[..2], 3),
+            Err(Error::InvalidSharingParams(_))
+        ));
+
+        let bytes = Share::to(&shares[0]);
+        assert_eq!(
+            <Share<G::Scalar> as FromBytes<_>>::from(&bytes).unwrap(),
+            shares[0]
+        );
+        let bytes = FeldmanCommitments::to(&commitments);
+        assert_eq!(
+            <FeldmanCommitments<G> as FromBytes<_>>::from(&bytes),
+            Ok(commitments)
+        );
+    }
+
+    fn corrupted_shares<G: CurveBackend>(params: &GroupParams<G>) {
+        let secret = SecretScalar::random(&mut OsRng);
+        let (shares, commitments) = split(params, &secret, 2, 3, &mut OsRng).unwrap();
+        let wrong_value = Share {
+            index: 1,
+            value: SecretScalar::new(*shares[0].value.expose_secret() + G::Scalar::ONE),
+        };
+        assert!(!wrong_value.verify(params, &commitments));
+        let wrong_index = Share {
+            index: 2,
+            value: SecretScalar::new(*shares[0].value.expose_secret()),
+        };
+        assert!(!wrong_index.verify(params, &commitments));
+        let zero_index = Share {
+            index: 0,
+            value: SecretScalar::new(*secret.expose_secret()),
+        };
+        assert!(!zero_index.verify(params, &commitments));
+
+        let (_, other) = split(params, &secret, 2, 3, &mut OsRng).unwrap();
+        assert!(!shares[0].verify(params, &other));
+        assert!(matches!(
+            split(params, &secret, 4, 3, &mut OsRng),
+            Err(Error::InvalidSharingParams(_))
+        ));
+        // The threshold must fit the two-byte count of the encoded commitments.
+        let t = u16::MAX as usize + 1;
+        assert!(matches!(
+            split(params, &secret, t, t as u32, &mut OsRng),
+            Err(Error::InvalidSharingParams(_))
+        ));
+        let repeated = [
+            wrong_index,
+            Share {
+                index: 2,
+                value: SecretScalar::new(G::Scalar::ONE),
+            },
+        ];
+        assert!(matches!(
+            recover(&repeated, 2),
+            Err(Error::InvalidSharingParams(_))
+        ));
+    }
+
+    #[test]
+    fn pallas_vss() {
+        share_and_recover::<pallas::Point>(&PALLAS_GROUP_PARAMS);
+        corrupted_shares::<pallas::Point>(&PALLAS_GROUP_PARAMS);
+    }
+
+    #[test]
+    fn vesta_vss() {
+        share_and_recover::<vesta::Point>(&VESTA_GROUP_PARAMS);
+        corrupted_shares::<vesta::Point>(&VESTA_GROUP_PARAMS);
+    }
+}
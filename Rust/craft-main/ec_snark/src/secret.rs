@@ -0,0 +1,140 @@
+use pasta_curves::group::ff::Field;
+use rand_core::{CryptoRng, RngCore};
+use std::fmt;
+use std::sync::atomic::{compiler_fence, Ordering};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Overwrites a field element with zero in a way the compiler does not elide.
+fn zeroize_field<F: Field>(value: &mut F) {
+    // SAFETY: `value` is a valid, aligned and exclusive reference.
+    unsafe { std::ptr::write_volatile(value, F::ZERO) };
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// A secret scalar, such as the discrete logarithm `x` of the DLEQ statement.
+///
+/// It is neither `Copy` nor `Clone`, is overwritten with zero when dropped, prints
+/// as `SecretScalar(<redacted>)` and is compared in constant time.
+pub struct SecretScalar<F: Field> {
+    value: F,
+}
+
+impl<F: Field> SecretScalar<F> {
+    /// Wraps a scalar.
+    pub fn new(value: F) -> Self {
+        Self { value }
+    }
+
+    /// Samples a uniformly random secret.
+    pub fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self::new(F::random(rng))
+    }
+
+    /// Returns a reference to the wrapped scalar.
+    ///
+    /// Every use of the returned value should be short-lived; copies of it are not zeroized.
+    pub fn expose_secret(&self) -> &F {
+        &self.value
+    }
+}
+
+/// The commitment randomness `k` of a single protocol run.
+///
+/// Revealing `k` together with the response `s = k + c * x` reveals `x`, so a nonce
+/// can only be consumed once: [`Nonce::consume`] takes it by value. Like
+/// [`SecretScalar`], it is zeroized on drop, redacted in `Debug` and compared in
+/// constant time.
+pub struct Nonce<F: Field> {
+    value: F,
+}
+
+impl<F: Field> Nonce<F> {
+    /// Wraps a scalar.
+    pub fn new(value: F) -> Self {
+        Self { value }
+    }
+
+    /// Samples a uniformly random nonce.
+    pub fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self::new(F::random(rng))
+    }
+
+    /// Returns a reference to the wrapped scalar without consuming the nonce.
+    pub fn expose_secret(&self) -> &F {
+        &self.value
+    }
+
+    /// Consumes the nonce and returns the wrapped scalar.
+    pub fn consume(mut self) -> F {
+        let value = self.value;
+        zeroize_field(&mut self.value);
+        value
+    }
+}
+
+macro_rules! impl_secret_traits {
+    ($name:ident) => {
+        impl<F: Field> Zeroize for $name<F> {
+            fn zeroize(&mut self) {
+                zeroize_field(&mut self.value);
+            }
+        }
+
+        impl<F: Field> Drop for $name<F> {
+            fn drop(&mut self) {
+                self.zeroize();
+            }
+        }
+
+        impl<F: Field> ZeroizeOnDrop for $name<F> {}
+
+        impl<F: Field> fmt::Debug for $name<F> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, concat!(stringify!($name), "(<redacted>)"))
+            }
+        }
+
+        impl<F: Field> ConstantTimeEq for $name<F> {
+            fn ct_eq(&self, other: &Self) -> Choice {
+                self.value.ct_eq(&other.value)
+            }
+        }
+
+        impl<F: Field> PartialEq for $name<F> {
+            fn eq(&self, other: &Self) -> bool {
+                self.ct_eq(other).into()
+            }
+        }
+
+        impl<F: Field> Eq for $name<F> {}
+    };
+}
+
+impl_secret_traits!(SecretScalar);
+impl_secret_traits!(Nonce);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pasta_curves::pallas::Scalar;
+
+    #[test]
+    fn debug_is_redacted() {
+        let x = SecretScalar::new(Scalar::from(42));
+        assert_eq!(format!("{:?}", x), "SecretScalar(<redacted>)");
+        let k = Nonce::new(Scalar::from(42));
+        assert_eq!(format!("{:?}", k), "Nonce(<redacted>)");
+    }
+
+    #[test]
+    fn zeroize_and_consume() {
+        let mut x = SecretScalar::new(Scalar::from(42));
+        assert_eq!(x, SecretScalar::new(Scalar::from(42)));
+        x.zeroize();
+        assert_eq!(*x.expose_secret(), Scalar::ZERO);
+
+        let k = Nonce::new(Scalar::from(7));
+        assert_eq!(k.consume(), Scalar::from(7));
+    }
+}
@@ -1,20 +1,431 @@
+use ec_snark::common::{FromBytes, IntoBytes};
+use ec_snark::protocol::elliptic_curves::dleq::DleqEllipticCurve;
+use ec_snark::protocol::elliptic_curves::CurveBackend;
+use ec_snark::protocol::fiat_shamir::{DleqProof, NonInteractiveProtocol};
+use ec_snark::protocol::sigma::and::And;
+use ec_snark::protocol::sigma::dleq::{Dleq, DleqStatement};
+use ec_snark::protocol::sigma::or::{Or, OrWitness};
+use ec_snark::protocol::sigma::schnorr::{Schnorr, SchnorrStatement};
+use ec_snark::protocol::sigma::{self, SigmaProtocol};
 use ec_snark::protocol::Protocol;
+use ec_snark::secret::SecretScalar;
+use ec_snark::Error;
+use pasta_curves::group::ff::{Field, PrimeField, PrimeFieldBits};
+use rand_chacha::rand_core::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rand_core::OsRng;
 
 pub fn run_protocol<T>(params: &T::GroupParameters, x: &T::Secret) -> bool
 where
     T: Protocol,
 {
     // The client calculates the commitment using their secret and the group parameters.
-    let (cp, k) = T::commitment(params, x);
+    let (cp, k) = T::commitment(params, x).unwrap();
 
     // The server (simulated here) sends a challenge to the client.
     let c = T::challenge(params);
 
     // The client calculates the response based on the commitment random, challenge,
     // and their secret.
-    let s = T::challenge_response(params, &k, &c, &x);
+    let s = T::challenge_response(params, k, &c, x);
 
     // The server (simulated here) verifies the response against the challenge and
     // commitment parameters.
-    T::verify(params, &s, &c, &cp)
+    T::verify(params, &s, &c, &cp).unwrap()
+}
+
+/// Test verification using standard protocol execution.
+pub fn success_verification<G>()
+where
+    G: CurveBackend + FromBytes<G>,
+{
+    let mut rng = OsRng;
+    let x = SecretScalar::random(&mut rng);
+    let params = G::group_params();
+    // Testing the correctness of the serialization and deserialization of group parameters.
+    let restored_g = G::from(&G::to(&params.g)).unwrap();
+    assert_eq!(params.g, restored_g);
+    let restored_h = G::from(&G::to(&params.h)).unwrap();
+    assert_eq!(params.h, restored_h);
+    // Asserting the successful execution of the protocol.
+    assert!(run_protocol::<DleqEllipticCurve<G>>(&params, &x));
+}
+
+/// Test verification fails with an incorrect response.
+pub fn fail_verification<G>()
+where
+    G: CurveBackend,
+{
+    let mut rng = OsRng;
+    let x = SecretScalar::random(&mut rng);
+    let params = G::group_params();
+    // Generating commitment and a challenge to simulate an authentication attempt.
+    let (cp, _) = DleqEllipticCurve::<G>::commitment(&params, &x).unwrap();
+    let c = DleqEllipticCurve::<G>::challenge(&params);
+    // Simulating a fake response to force a failed verification.
+    let fake_response = <G::Scalar as Field>::random(&mut rng);
+    // Asserting that the verification should fail with the fake response.
+    let verified = DleqEllipticCurve::<G>::verify(&params, &fake_response, &c, &cp).unwrap();
+    assert!(!verified);
+}
+
+/// Test a non-interactive proof verifies, also after a serialization round trip.
+pub fn non_interactive_verification<G>()
+where
+    G: CurveBackend + FromBytes<G>,
+    G::Scalar: FromBytes<G::Scalar> + IntoBytes<G::Scalar>,
+{
+    let mut rng = OsRng;
+    let x = SecretScalar::random(&mut rng);
+    let params = G::group_params();
+    let proof = DleqEllipticCurve::<G>::prove(&params, &x).unwrap();
+    assert_eq!(
+        DleqEllipticCurve::<G>::verify_proof(&params, &proof),
+        Ok(true)
+    );
+    let bytes = DleqProof::to(&proof);
+    let restored = <DleqProof<G, G::Scalar> as FromBytes<_>>::from(&bytes).unwrap();
+    assert_eq!(proof, restored);
+    assert!(matches!(
+        <DleqProof<G, G::Scalar> as FromBytes<_>>::from(&bytes[..bytes.len() - 1]),
+        Err(Error::InvalidLength { .. })
+    ));
+    assert_eq!(
+        DleqEllipticCurve::<G>::verify_proof(&params, &restored),
+        Ok(true)
+    );
+}
+
+/// Test a non-interactive proof fails once any part of it is altered.
+pub fn non_interactive_tampered_proof<G>()
+where
+    G: CurveBackend,
+{
+    let mut rng = OsRng;
+    let x = SecretScalar::random(&mut rng);
+    let params = G::group_params();
+    let proof = DleqEllipticCurve::<G>::prove(&params, &x).unwrap();
+
+    let mut forged = proof;
+    forged.s += G::Scalar::ONE;
+    assert_eq!(
+        DleqEllipticCurve::<G>::verify_proof(&params, &forged),
+        Ok(false)
+    );
+
+    let mut forged = proof;
+    forged.y1 += params.g;
+    assert_eq!(
+        DleqEllipticCurve::<G>::verify_proof(&params, &forged),
+        Ok(false)
+    );
+
+    let mut forged = proof;
+    forged.r2 += params.h;
+    assert_eq!(
+        DleqEllipticCurve::<G>::verify_proof(&params, &forged),
+        Ok(false)
+    );
+}
+
+/// Test batch verification accepts valid transcripts and reports the invalid ones.
+pub fn batch_verification<G: CurveBackend>() {
+    let mut rng = OsRng;
+    let params = G::group_params();
+    let mut transcripts: Vec<_> = (0..8)
+        .map(|_| {
+            let x = SecretScalar::random(&mut rng);
+            let (cp, k) = DleqEllipticCurve::<G>::commitment(&params, &x).unwrap();
+            let c = DleqEllipticCurve::<G>::challenge(&params);
+            let s = DleqEllipticCurve::<G>::challenge_response(&params, k, &c, &x);
+            (s, c, cp)
+        })
+        .collect();
+    assert_eq!(DleqEllipticCurve::<G>::verify_batch(&params, &[]), Ok(()));
+    assert_eq!(
+        DleqEllipticCurve::<G>::verify_batch(&params, &transcripts),
+        Ok(())
+    );
+
+    // A wrong response and a wrong commitment must both be pinpointed.
+    transcripts[1].0 += G::Scalar::ONE;
+    transcripts[6].2 .3 += params.h;
+    assert_eq!(
+        DleqEllipticCurve::<G>::verify_batch(&params, &transcripts),
+        Err(vec![1, 6])
+    );
+}
+
+/// Test malformed, non-canonical and identity encodings are rejected with precise errors.
+pub fn decoding_errors<G>()
+where
+    G: CurveBackend + FromBytes<G>,
+{
+    let params = G::group_params();
+    let bytes = G::to(&params.g);
+
+    let mut truncated = bytes.clone();
+    truncated.pop();
+    assert_eq!(
+        G::from(&truncated),
+        Err(Error::InvalidLength {
+            expected: bytes.len(),
+            actual: bytes.len() - 1,
+        })
+    );
+    assert_eq!(G::from(&G::to(&G::identity())), Err(Error::IdentityPoint));
+    assert!(matches!(
+        G::from(&vec![0xff; bytes.len()]),
+        Err(Error::OffCurvePoint | Error::NonCanonicalEncoding)
+    ));
+
+    let zero = SecretScalar::new(G::Scalar::ZERO);
+    assert_eq!(
+        DleqEllipticCurve::<G>::commitment(&params, &zero).map(|_| ()),
+        Err(Error::IdentityPoint)
+    );
+    let one = G::Scalar::ONE;
+    let cp = (G::identity(), G::identity(), G::identity(), G::identity());
+    assert_eq!(
+        DleqEllipticCurve::<G>::verify(&params, &one, &one, &cp),
+        Err(Error::IdentityPoint)
+    );
+    assert_eq!(
+        DleqEllipticCurve::<G>::verify_batch(&params, &[(one, one, cp)]),
+        Err(vec![0])
+    );
+}
+
+/// Test scalars are decoded only from their canonical encoding.
+pub fn scalar_decoding<G>()
+where
+    G: CurveBackend,
+    G::Scalar: FromBytes<G::Scalar> + IntoBytes<G::Scalar>,
+{
+    let scalar = G::hash_to_scalar("test", b"scalar");
+    let bytes = G::Scalar::to(&scalar);
+    assert_eq!(<G::Scalar as FromBytes<_>>::from(&bytes), Ok(scalar));
+
+    for len in [3, bytes.len() - 1, bytes.len() + 1, 64] {
+        assert_eq!(
+            <G::Scalar as FromBytes<_>>::from(&vec![0; len]),
+            Err(Error::InvalidLength {
+                expected: bytes.len(),
+                actual: len,
+            })
+        );
+    }
+    assert_eq!(
+        <G::Scalar as FromBytes<_>>::from(&vec![0xff; bytes.len()]),
+        Err(Error::NonCanonicalEncoding)
+    );
+
+    assert_eq!(G::hash_to_scalar("test", b"scalar"), scalar);
+    assert_ne!(G::hash_to_scalar("test", b"other"), scalar);
+    assert_ne!(G::hash_to_scalar("other", b"scalar"), scalar);
+}
+
+/// Test "knows one of these keys" and "knows both keys" proofs built from the sigma toolkit.
+pub fn sigma_composition<G: CurveBackend>() {
+    let mut rng = OsRng;
+    let params = G::group_params();
+    let keys: Vec<_> = (0..3).map(|_| SecretScalar::random(&mut rng)).collect();
+    let statements: Vec<_> = keys
+        .iter()
+        .map(|x| DleqStatement::new(&params, x))
+        .collect();
+
+    // One of N registered DLEQ credentials.
+    let witness = OrWitness {
+        index: 2,
+        witness: SecretScalar::new(*keys[2].expose_secret()),
+    };
+    let proof =
+        sigma::prove::<Or<Dleq<G>>, _>(b"delegate", &statements, &witness, &mut rng).unwrap();
+    assert_eq!(sigma::verify(b"delegate", &statements, &proof), Ok(true));
+    assert_eq!(
+        sigma::verify(b"delegate", &statements[..2].to_vec(), &proof),
+        Ok(false)
+    );
+
+    // The old DLEQ credential and a new Schnorr key, under one challenge.
+    let new_key = SecretScalar::random(&mut rng);
+    let statement = (statements[0], SchnorrStatement::new(params.g, &new_key));
+    let witness = (SecretScalar::new(*keys[0].expose_secret()), new_key);
+    type Rotation<G> = And<Dleq<G>, Schnorr<G>>;
+    let proof = sigma::prove::<Rotation<G>, _>(b"rotate", &statement, &witness, &mut rng).unwrap();
+    assert_eq!(sigma::verify(b"rotate", &statement, &proof), Ok(true));
+    assert_eq!(sigma::verify(b"delegate", &statement, &proof), Ok(false));
+
+    // Simulated transcripts are accepted by the interactive verifier.
+    let c = sigma::random_challenge::<Rotation<G>, _>(&mut rng);
+    let (commitment, response) = Rotation::<G>::simulate(&statement, &c, &mut rng).unwrap();
+    assert_eq!(
+        Rotation::<G>::verify(&statement, &commitment, &c, &response),
+        Ok(true)
+    );
+}
+
+/// Test the honest-verifier simulator produces accepting transcripts without the secret.
+pub fn simulated_transcripts<G>()
+where
+    G: CurveBackend,
+{
+    let mut rng = OsRng;
+    let params = G::group_params();
+    // Credentials of a secret nobody knows.
+    let y1 = G::hash_to_group("ec_snark-test", b"y1");
+    let y2 = G::hash_to_group("ec_snark-test", b"y2");
+    let transcript =
+        DleqEllipticCurve::<G>::simulate_with_rng(&params, &y1, &y2, &mut rng).unwrap();
+    assert_eq!(transcript.commit_parameters.0, y1);
+    assert_eq!(transcript.commit_parameters.1, y2);
+    assert!(DleqEllipticCurve::<G>::verify(
+        &params,
+        &transcript.response,
+        &transcript.challenge,
+        &transcript.commit_parameters
+    )
+    .unwrap());
+    // A simulated transcript is only accepted for the challenge it was made for.
+    let c = transcript.challenge + G::Scalar::ONE;
+    assert!(!DleqEllipticCurve::<G>::verify(
+        &params,
+        &transcript.response,
+        &c,
+        &transcript.commit_parameters
+    )
+    .unwrap());
+    assert_eq!(
+        DleqEllipticCurve::<G>::simulate(&params, &G::identity(), &y2),
+        Err(Error::IdentityPoint)
+    );
+}
+
+/// Number of transcripts of each kind compared by [`simulated_distribution`].
+const SAMPLES: usize = 256;
+
+/// Upper 0.01% quantile of the chi-square distribution with 15 degrees of freedom.
+const CHI_SQUARE_15: f64 = 42.58;
+
+/// Bound on the two-proportion z-statistic of a single bit, below an overall
+/// false positive rate of 0.01% across the bits of two full-width scalars.
+const Z_BOUND: f64 = 5.5;
+
+/// Two-sample chi-square statistic of equally sized samples of values in `0..16`.
+fn chi_square(a: &[u8], b: &[u8]) -> f64 {
+    let mut counts = [[0u32; 2]; 16];
+    for (sample, values) in [a, b].iter().enumerate() {
+        for v in values.iter() {
+            counts[*v as usize][sample] += 1;
+        }
+    }
+    counts
+        .iter()
+        .filter(|[x, y]| x + y > 0)
+        .map(|[x, y]| (*x as f64 - *y as f64).powi(2) / (x + y) as f64)
+        .sum()
+}
+
+/// Largest two-proportion z-statistic over the bits of equally sized samples of
+/// scalars, comparing how often each bit is set in `a` and in `b`.
+fn bit_bias<F: PrimeFieldBits>(a: &[F], b: &[F]) -> f64 {
+    let n = a.len() as f64;
+    let ones = |values: &[F]| {
+        let mut counts = vec![0u32; F::NUM_BITS as usize];
+        for v in values {
+            for (i, bit) in v.to_le_bits().iter().take(counts.len()).enumerate() {
+                counts[i] += *bit as u32;
+            }
+        }
+        counts
+    };
+    ones(a)
+        .iter()
+        .zip(ones(b).iter())
+        .map(|(x, y)| {
+            let (x, y) = (*x as f64, *y as f64);
+            let variance = (x + y) * (1.0 - (x + y) / (2.0 * n));
+            if variance > 0.0 {
+                (x - y).abs() / variance.sqrt()
+            } else {
+                0.0
+            }
+        })
+        .fold(0.0, f64::max)
+}
+
+/// Test simulated transcripts are distributed like real ones.
+///
+/// Every transcript, honest or simulated, must satisfy `r1 = g^s * y1^-c` and
+/// `r2 = h^s * y2^-c` for the same credentials. Given that, a transcript is fixed
+/// by `(c, s)`, so `c` and `s` are compared bit by bit over their full width and
+/// their joint distribution through the low nibble of `s - c`.
+pub fn simulated_distribution<G>()
+where
+    G: CurveBackend,
+    G::Scalar: IntoBytes<G::Scalar>,
+{
+    let mut rng = ChaCha20Rng::seed_from_u64(25);
+    let params = G::group_params();
+    let x = SecretScalar::random(&mut rng);
+    let (y1, y2) = (params.g * x.expose_secret(), params.h * x.expose_secret());
+    let nibble = |s: G::Scalar| G::Scalar::to(&s).last().unwrap() & 0x0f;
+    let consistent = |(y1_, y2_, r1, r2): (G, G, G, G), c: &G::Scalar, s: &G::Scalar| {
+        (y1_, y2_) == (y1, y2) && r1 == params.g * s - y1 * c && r2 == params.h * s - y2 * c
+    };
+
+    let real: Vec<_> = (0..SAMPLES)
+        .map(|_| {
+            let (cp, k) =
+                DleqEllipticCurve::<G>::commitment_with_rng(&params, &x, &mut rng).unwrap();
+            let c = DleqEllipticCurve::<G>::challenge_with_rng(&params, &mut rng);
+            let s = DleqEllipticCurve::<G>::challenge_response(&params, k, &c, &x);
+            assert!(consistent(cp, &c, &s));
+            (c, s)
+        })
+        .collect();
+    let simulated: Vec<_> = (0..SAMPLES)
+        .map(|_| {
+            let t = DleqEllipticCurve::<G>::simulate_with_rng(&params, &y1, &y2, &mut rng).unwrap();
+            assert!(consistent(t.commit_parameters, &t.challenge, &t.response));
+            assert!(DleqEllipticCurve::<G>::verify(
+                &params,
+                &t.response,
+                &t.challenge,
+                &t.commit_parameters
+            )
+            .unwrap());
+            (t.challenge, t.response)
+        })
+        .collect();
+
+    let challenges = |t: &[(G::Scalar, G::Scalar)]| t.iter().map(|(c, _)| *c).collect::<Vec<_>>();
+    let responses = |t: &[(G::Scalar, G::Scalar)]| t.iter().map(|(_, s)| *s).collect::<Vec<_>>();
+    let offsets =
+        |t: &[(G::Scalar, G::Scalar)]| t.iter().map(|(c, s)| nibble(*s - c)).collect::<Vec<_>>();
+    let z = bit_bias(&challenges(&real), &challenges(&simulated));
+    assert!(z < Z_BOUND, "challenge: {}", z);
+    let z = bit_bias(&responses(&real), &responses(&simulated));
+    assert!(z < Z_BOUND, "response: {}", z);
+    let statistic = chi_square(&offsets(&real), &offsets(&simulated));
+    assert!(statistic < CHI_SQUARE_15, "offset: {}", statistic);
+
+    // The checks tell broken simulators apart. Drawing r2 independently of
+    // (c, s) breaks the joint structure.
+    let (c, s) = simulated[0];
+    let r2 = G::random(&mut rng);
+    assert!(!consistent((y1, y2, params.g * s - y1 * c, r2), &c, &s));
+    // Drawing s from the low 128 bits only biases its high bits.
+    let short: Vec<_> = (0..SAMPLES)
+        .map(|_| G::Scalar::from_u128((rng.next_u64() as u128) << 64 | rng.next_u64() as u128))
+        .collect();
+    assert!(bit_bias(&responses(&real), &short) > Z_BOUND);
+    // Deriving s from c leaves the marginals uniform but fixes s - c.
+    let derived: Vec<_> = challenges(&simulated)
+        .into_iter()
+        .map(|c| (c, c + G::Scalar::ONE))
+        .collect();
+    assert!(bit_bias(&responses(&real), &responses(&derived)) < Z_BOUND);
+    assert!(chi_square(&offsets(&real), &offsets(&derived)) > CHI_SQUARE_15);
 }
@@ -0,0 +1,63 @@
+mod common;
+
+/// Instantiates the shared functional test suite for a curve backend.
+macro_rules! functional_tests {
+    ($curve:ident, $point:ty) => {
+        mod $curve {
+            #[test]
+            fn success_verification() {
+                crate::common::success_verification::<$point>();
+            }
+
+            #[test]
+            fn fail_verification() {
+                crate::common::fail_verification::<$point>();
+            }
+
+            #[test]
+            fn non_interactive_verification() {
+                crate::common::non_interactive_verification::<$point>();
+            }
+
+            #[test]
+            fn non_interactive_tampered_proof() {
+                crate::common::non_interactive_tampered_proof::<$point>();
+            }
+
+            #[test]
+            fn batch_verification() {
+                crate::common::batch_verification::<$point>();
+            }
+
+            #[test]
+            fn decoding_errors() {
+                crate::common::decoding_errors::<$point>();
+            }
+
+            #[test]
+            fn scalar_decoding() {
+                crate::common::scalar_decoding::<$point>();
+            }
+
+            #[test]
+            fn sigma_composition() {
+                crate::common::sigma_composition::<$point>();
+            }
+
+            #[test]
+            fn simulated_transcripts() {
+                crate::common::simulated_transcripts::<$point>();
+            }
+
+            #[test]
+            fn simulated_distribution() {
+                crate::common::simulated_distribution::<$point>();
+            }
+        }
+    };
+}
+
+functional_tests!(pallas, pasta_curves::pallas::Point);
+functional_tests!(vesta, pasta_curves::vesta::Point);
+functional_tests!(ristretto, curve25519_dalek::ristretto::RistrettoPoint);
+functional_tests!(bls12381, bls12_381::G1Projective);
@@ -0,0 +1,91 @@
+use ec_snark::common::{FromBytes, IntoBytes};
+use ec_snark::protocol::elliptic_curves::dleq::DleqEllipticCurve;
+use ec_snark::protocol::elliptic_curves::CurveBackend;
+use ec_snark::protocol::fiat_shamir::{DleqProof, NonInteractiveProtocol};
+use ec_snark::protocol::Protocol;
+use ec_snark::secret::SecretScalar;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use serde::Deserialize;
+
+/// Known-answer tests of the DLEQ protocol, see `vectors/dleq_v1.json`.
+///
+/// Every vector seeds a `ChaCha20Rng`, draws the nonce `k` with `commitment_with_rng`
+/// and then the challenge `c` with `challenge_with_rng` from the same generator. The
+/// non-interactive proof is made with a generator freshly seeded with the same seed.
+/// All values are hex encodings as produced by `IntoBytes`.
+#[derive(Deserialize)]
+struct VectorFile {
+    version: u32,
+    vectors: Vec<Vector>,
+}
+
+#[derive(Deserialize)]
+struct Vector {
+    curve: String,
+    seed: String,
+    x: String,
+    y1: String,
+    y2: String,
+    r1: String,
+    r2: String,
+    k: String,
+    c: String,
+    s: String,
+    proof: String,
+}
+
+const VECTORS: &str = include_str!("vectors/dleq_v1.json");
+
+fn replay<G>(vector: &Vector)
+where
+    G: CurveBackend + FromBytes<G>,
+    G::Scalar: FromBytes<G::Scalar> + IntoBytes<G::Scalar>,
+{
+    let params = G::group_params();
+    let seed: [u8; 32] = hex::decode(&vector.seed).unwrap().try_into().unwrap();
+    let x = <G::Scalar as FromBytes<_>>::from(&hex::decode(&vector.x).unwrap()).unwrap();
+    let x = SecretScalar::new(x);
+
+    let mut rng = ChaCha20Rng::from_seed(seed);
+    let ((y1, y2, r1, r2), k) =
+        DleqEllipticCurve::<G>::commitment_with_rng(&params, &x, &mut rng).unwrap();
+    let c = DleqEllipticCurve::<G>::challenge_with_rng(&params, &mut rng);
+    assert_eq!(hex::encode(G::Scalar::to(k.expose_secret())), vector.k);
+    let s = DleqEllipticCurve::<G>::challenge_response(&params, k, &c, &x);
+
+    assert_eq!(hex::encode(G::to(&y1)), vector.y1);
+    assert_eq!(hex::encode(G::to(&y2)), vector.y2);
+    assert_eq!(hex::encode(G::to(&r1)), vector.r1);
+    assert_eq!(hex::encode(G::to(&r2)), vector.r2);
+    assert_eq!(hex::encode(G::Scalar::to(&c)), vector.c);
+    assert_eq!(hex::encode(G::Scalar::to(&s)), vector.s);
+    assert_eq!(
+        DleqEllipticCurve::<G>::verify(&params, &s, &c, &(y1, y2, r1, r2)),
+        Ok(true)
+    );
+
+    let mut rng = ChaCha20Rng::from_seed(seed);
+    let proof = DleqEllipticCurve::<G>::prove_with_rng(&params, &x, &mut rng).unwrap();
+    assert_eq!(hex::encode(DleqProof::to(&proof)), vector.proof);
+    let bytes = hex::decode(&vector.proof).unwrap();
+    let decoded = <DleqProof<G, G::Scalar> as FromBytes<_>>::from(&bytes).unwrap();
+    assert_eq!(
+        DleqEllipticCurve::<G>::verify_proof(&params, &decoded),
+        Ok(true)
+    );
+}
+
+#[test]
+fn dleq_known_answers() {
+    let file: VectorFile = serde_json::from_str(VECTORS).unwrap();
+    assert_eq!(file.version, 1);
+    assert!(!file.vectors.is_empty());
+    for vector in &file.vectors {
+        match vector.curve.as_str() {
+            "pallas" => replay::<pasta_curves::pallas::Point>(vector),
+            "vesta" => replay::<pasta_curves::vesta::Point>(vector),
+            curve => panic!("no backend for test vectors of curve {}", curve),
+        }
+    }
+}
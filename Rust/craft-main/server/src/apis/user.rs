@@ -4,15 +4,15 @@
 /// - `username`: A `String` representing the username of the user.
 /// - `y1`: A generic field of type `T`.
 /// - `y2`: Another generic field of type `T`.
-/// - `r1`: An `Option<T>` representing an optional field of type `T`.
-/// - `r2`: Another `Option<T>` representing an optional field of type `T`.
+/// - `salt`: The salt the user's secret is derived with, returned to the client at login.
+/// - `rotations`: The number of accepted credential rotations, which rotation proofs are bound to.
 #[derive(Debug, Clone)]
 pub struct User<T> {
     pub username: String,
+    pub salt: Vec<u8>,
     pub y1: T,
     pub y2: T,
-    pub r1: Option<T>,
-    pub r2: Option<T>,
+    pub rotations: u64,
 }
 
 /// Represents an challenge for a user.
@@ -20,11 +20,15 @@ pub struct User<T> {
 /// # Fields
 /// - `id`: A `String` representing the unique identifier of the challenge.
 /// - `user`: A `String` representing the username of the user this challenge is associated with.
+/// - `r1`: The user's first commitment, which the challenge was issued for.
+/// - `r2`: The user's second commitment, which the challenge was issued for.
 /// - `c`: A generic field of type `S` representing the challenge data.
 #[derive(Debug, Clone)]
-pub struct Challenge<S> {
+pub struct Challenge<T, S> {
     pub id: String,
     pub user: String,
+    pub r1: T,
+    pub r2: T,
     pub c: S,
 }
 
@@ -41,13 +45,13 @@ pub trait UserAPI<T, S> {
 
     fn read(&mut self, username: &str) -> Option<User<T>>;
 
-    fn update(&mut self, name: &String, user: User<T>) -> Option<()>;
+    fn update(&mut self, name: &str, user: User<T>) -> Option<()>;
 
-    fn delete(&mut self, name: &String) -> Option<User<T>>;
+    fn delete(&mut self, name: &str) -> Option<User<T>>;
 
-    fn create_challenge(&mut self, user: &String, c: &S) -> String;
+    fn create_challenge(&mut self, user: &str, r1: &T, r2: &T, c: &S) -> String;
 
-    fn get_challenge(&mut self, id: &String) -> Option<Challenge<S>>;
+    fn get_challenge(&mut self, id: &str) -> Option<Challenge<T, S>>;
 
-    fn delete_challenge(&mut self, id: &String);
+    fn delete_challenge(&mut self, id: &str);
 }
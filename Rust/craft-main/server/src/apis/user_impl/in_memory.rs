@@ -9,7 +9,7 @@ use crate::apis::user::Challenge;
 /// A struct representing an in-memory User struct.
 pub struct InMemoryUserAPI<T, S> {
     users: HashMap<String, User<T>>,
-    challenges: HashMap<String, Challenge<S>>,
+    challenges: HashMap<String, Challenge<T, S>>,
 }
 
 impl<T, S> InMemoryUserAPI<T, S> {
@@ -21,6 +21,12 @@ impl<T, S> InMemoryUserAPI<T, S> {
     }
 }
 
+impl<T, S> Default for InMemoryUserAPI<T, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T, S> UserAPI<T, S> for InMemoryUserAPI<T, S>
 where
     T: Send + Sync + 'static + Clone + FromBytes<T> + IntoBytes<T>,
@@ -34,7 +40,7 @@ where
         self.users.get(username).cloned()
     }
 
-    fn update(&mut self, name: &String, new_user: User<T>) -> Option<()> {
+    fn update(&mut self, name: &str, new_user: User<T>) -> Option<()> {
         if let Some(user) = self.users.get_mut(name) {
             *user = new_user;
             Some(())
@@ -43,26 +49,28 @@ where
         }
     }
 
-    fn delete(&mut self, name: &String) -> Option<User<T>> {
+    fn delete(&mut self, name: &str) -> Option<User<T>> {
         self.users.remove(name)
     }
 
-    fn create_challenge(&mut self, user: &String, c: &S) -> String {
+    fn create_challenge(&mut self, user: &str, r1: &T, r2: &T, c: &S) -> String {
         let uid = Uuid::new_v4().to_string();
         let auth_challenge = Challenge {
             id: uid.clone(),
-            user: user.clone(),
+            user: user.to_string(),
+            r1: r1.clone(),
+            r2: r2.clone(),
             c: c.clone(),
         };
         self.challenges.insert(uid.clone(), auth_challenge);
         uid
     }
 
-    fn delete_challenge(&mut self, id: &String) {
+    fn delete_challenge(&mut self, id: &str) {
         self.challenges.remove(id);
     }
 
-    fn get_challenge(&mut self, id: &String) -> Option<Challenge<S>> {
+    fn get_challenge(&mut self, id: &str) -> Option<Challenge<T, S>> {
         self.challenges.get(id).cloned()
     }
 }
@@ -3,8 +3,13 @@ pub mod service;
 /// CRUD APIs to work with storage.
 pub mod apis;
 
+use curve25519_dalek::ristretto::RistrettoPoint;
 use ec_snark::common::EllipticCurve;
+use ec_snark::protocol::elliptic_curves::bls12381::{
+    Bls12381EllipticCurve, Point as Bls12381Point,
+};
 use ec_snark::protocol::elliptic_curves::pallas::PallasEllipticCurve;
+use ec_snark::protocol::elliptic_curves::ristretto::RistrettoEllipticCurve;
 use ec_snark::protocol::elliptic_curves::vesta::VestaEllipticCurve;
 use ec_snark::protocol::GroupParams;
 use pasta_curves::pallas::Point as PallasPoint;
@@ -26,18 +31,27 @@ struct Cli {
 
     #[structopt(short, long, possible_values = EllipticCurve::VARIANTS, default_value = "pallas")]
     curve: EllipticCurve,
+
+    /// Stable identity of this server, which clients derive their secret for.
+    /// Use a distinct one per deployment and keep it while users are registered.
+    #[structopt(long)]
+    service_id: String,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let Cli {
-        host, port, curve, ..
+        host,
+        port,
+        curve,
+        service_id,
     } = Cli::from_args();
 
     println!(" Starting server ");
     println!("       host: {}", host);
     println!("       port: {}", port);
     println!("       elliptic curve: {}", curve);
+    println!("       service id: {}", service_id);
 
     let addr = format!("{}:{}", host, port)
         .parse()
@@ -45,9 +59,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     match curve {
         EllipticCurve::Pallas => {
-            let params = GroupParams::<PallasPoint>::from_str(&curve.to_string())
-                .map_err(|_| "Invalid group parameters value".to_string())?;
-            let auth = ECAuthentication::<PallasEllipticCurve, _, _>::new(params);
+            let params = GroupParams::<PallasPoint>::from_str(&curve.to_string())?;
+            params.validate()?;
+            let auth = ECAuthentication::<PallasEllipticCurve, _, _>::new(params, service_id);
             Server::builder()
                 .add_service(AuthenticationServer::new(auth))
                 .serve(addr)
@@ -55,9 +69,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
 
         EllipticCurve::Vesta => {
-            let params = GroupParams::<VestaPoint>::from_str(&curve.to_string())
-                .map_err(|_| "Invalid elliptic curve values".to_string())?;
-            let auth = ECAuthentication::<VestaEllipticCurve, _, _>::new(params);
+            let params = GroupParams::<VestaPoint>::from_str(&curve.to_string())?;
+            params.validate()?;
+            let auth = ECAuthentication::<VestaEllipticCurve, _, _>::new(params, service_id);
+            Server::builder()
+                .add_service(AuthenticationServer::new(auth))
+                .serve(addr)
+                .await?;
+        }
+
+        EllipticCurve::Ristretto => {
+            let params = GroupParams::<RistrettoPoint>::from_str(&curve.to_string())?;
+            params.validate()?;
+            let auth = ECAuthentication::<RistrettoEllipticCurve, _, _>::new(params, service_id);
+            Server::builder()
+                .add_service(AuthenticationServer::new(auth))
+                .serve(addr)
+                .await?;
+        }
+
+        EllipticCurve::Bls12381 => {
+            let params = GroupParams::<Bls12381Point>::from_str(&curve.to_string())?;
+            params.validate()?;
+            let auth = ECAuthentication::<Bls12381EllipticCurve, _, _>::new(params, service_id);
             Server::builder()
                 .add_service(AuthenticationServer::new(auth))
                 .serve(addr)
@@ -1,7 +1,11 @@
 use crate::apis::user_impl::in_memory::InMemoryUserAPI;
 use crate::apis::{user::User, user::UserAPI};
 use ec_snark::common::{FromBytes, IntoBytes};
+use ec_snark::kdf::SALT_LEN;
+use ec_snark::protocol::elliptic_curves::CurveBackend;
+use ec_snark::protocol::rotation::{rotation_context, verify_rotation, RotationProof};
 use ec_snark::protocol::{GroupParams, Protocol};
+use ec_snark::Error;
 use log::{debug, error, info, trace};
 use tokio::sync::Mutex;
 use tonic::{Request, Response, Status};
@@ -14,13 +18,42 @@ pub mod ec_auth {
 
 // Protobuf imports
 use ec_auth::{
-    authentication_server::Authentication, AnswerRequest, AnswerResponse,
-    ChallengeRequest, ChallengeResponse, RegisterRequest,
-    RegisterResponse,
+    authentication_server::Authentication, AnswerRequest, AnswerResponse, ChallengeRequest,
+    ChallengeResponse, RegisterRequest, RegisterResponse, RotateRequest, RotateResponse,
+    SaltRequest, SaltResponse, ServiceIdRequest, ServiceIdResponse,
 };
 
+/// Checks that a salt has the length the client's key derivation uses.
+fn check_salt(salt: &[u8]) -> Result<(), Error> {
+    if salt.len() != SALT_LEN {
+        return Err(Error::InvalidLength {
+            expected: SALT_LEN,
+            actual: salt.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Maps a protocol error on a request field to the matching gRPC status.
+fn into_status(field: &str, err: Error) -> Status {
+    match err {
+        Error::InvalidLength { .. }
+        | Error::NonCanonicalEncoding
+        | Error::IdentityPoint
+        | Error::OffCurvePoint
+        | Error::InvalidKdfParams(_)
+        | Error::InvalidRangeParams(_)
+        | Error::InvalidProof
+        | Error::InvalidSharingParams(_)
+        | Error::InvalidWitness => Status::invalid_argument(format!("Invalid {}: {}", field, err)),
+        Error::UnknownCurve(_) => Status::unimplemented(err.to_string()),
+        Error::InvalidGroupParams(_) => Status::internal(err.to_string()),
+    }
+}
+
 pub struct ECAuthentication<C, T, S> {
     params: GroupParams<T>,
+    service_id: String,
     api: Mutex<Box<dyn UserAPI<T, S> + Send + Sync>>,
     _type_phantom: std::marker::PhantomData<C>,
     _scalar_phantom: std::marker::PhantomData<S>,
@@ -42,12 +75,18 @@ impl<
             + 'static,
     > ECAuthentication<C, T, S>
 {
-    pub fn new(params: GroupParams<T>) -> Self {
+    /// Creates the service for the given group parameters.
+    ///
+    /// `service_id` is the stable identity clients derive their per-service secret
+    /// for. It must not change while users are registered, and should differ
+    /// between deployments so that their registrations cannot be linked.
+    pub fn new(params: GroupParams<T>, service_id: String) -> Self {
         let api = Mutex::new(
             Box::new(InMemoryUserAPI::<T, S>::new()) as Box<dyn UserAPI<T, S> + Send + Sync>
         );
         Self {
             params,
+            service_id,
             api,
             _type_phantom: std::marker::PhantomData,
             _scalar_phantom: std::marker::PhantomData,
@@ -58,13 +97,11 @@ impl<
 #[tonic::async_trait]
 impl<C, T, S> Authentication for ECAuthentication<C, T, S>
 where
-    T: Send + Sync + 'static + Clone + FromBytes<T> + IntoBytes<T>,
+    T: Send + Sync + 'static + Clone + FromBytes<T> + IntoBytes<T> + CurveBackend<Scalar = S>,
     S: Send + Sync + 'static + Clone + FromBytes<S> + IntoBytes<S>,
     C: Protocol<
             Response = S,
-            CommitmentRandom = S,
             Challenge = S,
-            Secret = S,
             GroupParameters = GroupParams<T>,
             CommitParameters = (T, T, T, T),
         >
@@ -79,18 +116,23 @@ where
         trace!("register_user: {:?}", request);
         let req = request.into_inner();
 
-        let y1 = T::from(&req.y1).or_else(|_| Err(Status::invalid_argument("Invalid y1")))?;
-        let y2 = T::from(&req.y2).or_else(|_| Err(Status::invalid_argument("Invalid y2")))?;
+        let y1 = T::from(&req.y1).map_err(|e| into_status("y1", e))?;
+        let y2 = T::from(&req.y2).map_err(|e| into_status("y2", e))?;
+        check_salt(&req.salt).map_err(|e| into_status("salt", e))?;
 
         let user = User {
             username: req.user.clone(),
+            salt: req.salt,
             y1,
             y2,
-            r1: None,
-            r2: None,
+            rotations: 0,
         };
 
+        // Registered credentials can only be changed with RotateCredentials.
         let mut api = self.api.lock().await;
+        if api.read(&user.username).is_some() {
+            return Err(Status::already_exists("User already registered"));
+        }
         api.create(user);
 
         let reply = RegisterResponse {};
@@ -98,6 +140,39 @@ where
         Ok(Response::new(reply))
     }
 
+    async fn get_salt(
+        &self,
+        request: Request<SaltRequest>,
+    ) -> Result<Response<SaltResponse>, Status> {
+        trace!("get_salt: {:?}", request);
+        let req = request.into_inner();
+
+        let user = {
+            let mut api = self.api.lock().await;
+            api.read(&req.user)
+                .ok_or_else(|| Status::not_found("User not found"))?
+        };
+
+        let reply = SaltResponse {
+            salt: user.salt,
+            rotations: user.rotations,
+        };
+        trace!("get_salt reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    async fn get_service_id(
+        &self,
+        request: Request<ServiceIdRequest>,
+    ) -> Result<Response<ServiceIdResponse>, Status> {
+        trace!("get_service_id: {:?}", request);
+        let reply = ServiceIdResponse {
+            service_id: self.service_id.clone(),
+        };
+        trace!("get_service_id reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
     async fn create_challenge(
         &self,
         request: Request<ChallengeRequest>,
@@ -106,22 +181,16 @@ where
         let req = request.into_inner();
         let challenge = C::challenge(&self.params);
 
-        let user = {
-            let mut api = self.api.lock().await;
-            let mut user = api
-                .read(&req.user)
-                .ok_or_else(|| Status::not_found("User not found"))?;
-            user.r1 =
-                Some(T::from(&req.r1).or_else(|_| Err(Status::invalid_argument("Invalid r1")))?);
-            user.r2 =
-                Some(T::from(&req.r2).or_else(|_| Err(Status::invalid_argument("Invalid r2")))?);
-            user.clone()
-        };
+        let r1 = T::from(&req.r1).map_err(|e| into_status("r1", e))?;
+        let r2 = T::from(&req.r2).map_err(|e| into_status("r2", e))?;
 
+        // The commitment is stored with the challenge, so that later challenges
+        // for the same user cannot replace it before this one is answered.
         let auth_id = {
             let mut api = self.api.lock().await;
-            api.update(&user.username, user.clone());
-            api.create_challenge(&req.user, &challenge)
+            api.read(&req.user)
+                .ok_or_else(|| Status::not_found("User not found"))?;
+            api.create_challenge(&req.user, &r1, &r2, &challenge)
         };
 
         let reply = ChallengeResponse {
@@ -151,14 +220,11 @@ where
                 .ok_or_else(|| Status::not_found("User not found"))?
         };
 
-        let s = S::from(&req.s).or_else(|_| Err(Status::invalid_argument("Invalid s")))?;
-        let params = self.params.clone();
-        let verified = C::verify(
-            &params,
-            &s,
-            &challenge.c,
-            &(user.y1, user.y2, user.r1.unwrap(), user.r2.unwrap()),
-        );
+        let s = <S as FromBytes<S>>::from(&req.s).map_err(|e| into_status("s", e))?;
+        let params = self.params;
+        let commitment = (user.y1, user.y2, challenge.r1, challenge.r2);
+        let verified = C::verify(&params, &s, &challenge.c, &commitment)
+            .map_err(|e| into_status("commitment", e))?;
 
         debug!("User: {} verified", user.username);
         if !verified {
@@ -178,4 +244,277 @@ where
         trace!("verify_authentication reply: {:?}", reply);
         Ok(Response::new(reply))
     }
+
+    async fn rotate_credentials(
+        &self,
+        request: Request<RotateRequest>,
+    ) -> Result<Response<RotateResponse>, Status> {
+        trace!("rotate_credentials: {:?}", request);
+        let req = request.into_inner();
+
+        let y1 = T::from(&req.y1).map_err(|e| into_status("y1", e))?;
+        let y2 = T::from(&req.y2).map_err(|e| into_status("y2", e))?;
+        check_salt(&req.salt).map_err(|e| into_status("salt", e))?;
+        let proof = <RotationProof<T> as FromBytes<_>>::from(&req.proof)
+            .map_err(|e| into_status("proof", e))?;
+
+        // The lock is held from the check to the update, so concurrent rotations
+        // cannot both succeed against the same stored credentials.
+        let mut api = self.api.lock().await;
+        let user = api
+            .read(&req.user)
+            .ok_or_else(|| Status::not_found("User not found"))?;
+        let verified = verify_rotation(
+            &self.params,
+            &rotation_context(&req.user, &req.salt, user.rotations),
+            &(user.y1, user.y2),
+            &(y1, y2),
+            &proof,
+        )
+        .map_err(|e| into_status("proof", e))?;
+        if !verified {
+            error!("Invalid rotation proof for user: {}", user.username);
+            return Err(Status::permission_denied("Invalid rotation proof"));
+        }
+
+        let rotated = User {
+            username: user.username.clone(),
+            salt: req.salt,
+            y1,
+            y2,
+            rotations: user.rotations + 1,
+        };
+        api.update(&user.username, rotated);
+
+        info!(" User: {} rotated credentials", user.username);
+        let reply = RotateResponse {};
+        trace!("rotate_credentials reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use curve25519_dalek::ristretto::RistrettoPoint;
+    use ec_snark::protocol::elliptic_curves::dleq::{DleqEllipticCurve, DleqTranscript};
+    use ec_snark::protocol::rotation::prove_rotation;
+    use ec_snark::protocol::sigma::dleq::{Dleq, DleqStatement};
+    use ec_snark::protocol::sigma::SigmaProtocol;
+    use ec_snark::secret::SecretScalar;
+    use pasta_curves::group::ff::Field;
+    use pasta_curves::group::Group;
+    use pasta_curves::pallas;
+    use rand_core::OsRng;
+    use tonic::Code;
+
+    type Service<T> = ECAuthentication<DleqEllipticCurve<T>, T, <T as Group>::Scalar>;
+
+    /// Registers credentials of a secret the test never learns.
+    async fn register<T>(auth: &Service<T>, user: &str) -> (T, T)
+    where
+        T: CurveBackend + FromBytes<T>,
+        T::Scalar: FromBytes<T::Scalar> + IntoBytes<T::Scalar>,
+    {
+        let y1 = T::hash_to_group("craft-server-test", b"y1");
+        let y2 = T::hash_to_group("craft-server-test", b"y2");
+        let request = RegisterRequest {
+            user: user.to_string(),
+            y1: T::to(&y1),
+            y2: T::to(&y2),
+            salt: vec![0; SALT_LEN],
+        };
+        auth.register_user(Request::new(request)).await.unwrap();
+        (y1, y2)
+    }
+
+    /// Issues the challenge of a transcript for its commitment, as if
+    /// `create_challenge` had drawn it.
+    async fn open_challenge<T>(auth: &Service<T>, user: &str, t: &DleqTranscript<T>) -> String
+    where
+        T: CurveBackend + FromBytes<T>,
+        T::Scalar: FromBytes<T::Scalar> + IntoBytes<T::Scalar>,
+    {
+        let (_, _, r1, r2) = &t.commit_parameters;
+        let mut api = auth.api.lock().await;
+        api.create_challenge(user, r1, r2, &t.challenge)
+    }
+
+    async fn answer<T>(auth: &Service<T>, auth_id: &str, s: &T::Scalar) -> Result<String, Code>
+    where
+        T: CurveBackend + FromBytes<T>,
+        T::Scalar: FromBytes<T::Scalar> + IntoBytes<T::Scalar>,
+    {
+        let request = AnswerRequest {
+            auth_id: auth_id.to_string(),
+            s: <T::Scalar as IntoBytes<T::Scalar>>::to(s),
+        };
+        auth.verify(Request::new(request))
+            .await
+            .map(|reply| reply.into_inner().session_id)
+            .map_err(|status| status.code())
+    }
+
+    async fn simulated_login<T>()
+    where
+        T: CurveBackend + FromBytes<T>,
+        T::Scalar: FromBytes<T::Scalar> + IntoBytes<T::Scalar>,
+    {
+        let params = T::group_params();
+        let auth = Service::<T>::new(params, "craft-test".to_string());
+        let (y1, y2) = register(&auth, "peggy").await;
+
+        let transcript = DleqEllipticCurve::<T>::simulate(&params, &y1, &y2).unwrap();
+        let auth_id = open_challenge(&auth, "peggy", &transcript).await;
+        assert!(answer(&auth, &auth_id, &transcript.response).await.is_ok());
+        // The challenge is consumed by a successful answer.
+        assert_eq!(
+            answer(&auth, &auth_id, &transcript.response).await,
+            Err(Code::NotFound)
+        );
+
+        let transcript = DleqEllipticCurve::<T>::simulate(&params, &y1, &y2).unwrap();
+        let auth_id = open_challenge(&auth, "peggy", &transcript).await;
+        let tampered = transcript.response + T::Scalar::ONE;
+        assert_eq!(
+            answer(&auth, &auth_id, &tampered).await,
+            Err(Code::InvalidArgument)
+        );
+        assert!(answer(&auth, &auth_id, &transcript.response).await.is_ok());
+
+        // A transcript simulated for other credentials is rejected.
+        let other = T::hash_to_group("craft-server-test", b"other");
+        let transcript = DleqEllipticCurve::<T>::simulate(&params, &other, &y2).unwrap();
+        let auth_id = open_challenge(&auth, "peggy", &transcript).await;
+        assert_eq!(
+            answer(&auth, &auth_id, &transcript.response).await,
+            Err(Code::InvalidArgument)
+        );
+    }
+
+    #[tokio::test]
+    async fn pallas_simulated_login() {
+        simulated_login::<pallas::Point>().await;
+    }
+
+    #[tokio::test]
+    async fn ristretto_simulated_login() {
+        simulated_login::<RistrettoPoint>().await;
+    }
+
+    #[tokio::test]
+    async fn commitment_bound_to_challenge() {
+        let params = pallas::Point::group_params();
+        let auth = Service::<pallas::Point>::new(params, "craft-test".to_string());
+        let (y1, y2) = register(&auth, "peggy").await;
+        let challenge = |r1: &pallas::Point, r2: &pallas::Point| ChallengeRequest {
+            user: "peggy".to_string(),
+            r1: pallas::Point::to(r1),
+            r2: pallas::Point::to(r2),
+        };
+
+        // Knowing only the username, open a challenge and simulate an answer to it.
+        let r = pallas::Point::hash_to_group("craft-server-test", b"r");
+        let first = auth
+            .create_challenge(Request::new(challenge(&r, &r)))
+            .await
+            .unwrap()
+            .into_inner();
+        let c = <pallas::Scalar as FromBytes<_>>::from(&first.c).unwrap();
+        let statement = DleqStatement {
+            g: params.g,
+            h: params.h,
+            y1,
+            y2,
+        };
+        let ((r1, r2), s) = Dleq::simulate(&statement, &c, &mut OsRng).unwrap();
+
+        // A second challenge does not replace the commitment of the first.
+        auth.create_challenge(Request::new(challenge(&r1, &r2)))
+            .await
+            .unwrap();
+        assert_eq!(
+            answer(&auth, &first.auth_id, &s).await,
+            Err(Code::InvalidArgument)
+        );
+    }
+
+    #[tokio::test]
+    async fn service_id() {
+        let params = pallas::Point::group_params();
+        let auth = Service::<pallas::Point>::new(params, "craft-test".to_string());
+        let reply = auth.get_service_id(Request::new(ServiceIdRequest {})).await;
+        assert_eq!(reply.unwrap().into_inner().service_id, "craft-test");
+    }
+
+    #[tokio::test]
+    async fn register_twice() {
+        let params = pallas::Point::group_params();
+        let auth = Service::<pallas::Point>::new(params, "craft-test".to_string());
+        register(&auth, "peggy").await;
+        let request = RegisterRequest {
+            user: "peggy".to_string(),
+            y1: pallas::Point::to(&pallas::Point::generator()),
+            y2: pallas::Point::to(&pallas::Point::generator()),
+            salt: vec![0; SALT_LEN],
+        };
+        let status = auth.register_user(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), Code::AlreadyExists);
+    }
+
+    /// A request to rotate `peggy`'s credentials from `old` to `new` after `rotations`.
+    fn rotate_request(
+        params: &GroupParams<pallas::Point>,
+        rotations: u64,
+        old: &SecretScalar<pallas::Scalar>,
+        new: &SecretScalar<pallas::Scalar>,
+    ) -> RotateRequest {
+        let salt = vec![0; SALT_LEN];
+        let context = rotation_context("peggy", &salt, rotations);
+        let ((y1, y2), proof) = prove_rotation(params, &context, old, new, &mut OsRng).unwrap();
+        RotateRequest {
+            user: "peggy".to_string(),
+            y1: pallas::Point::to(&y1),
+            y2: pallas::Point::to(&y2),
+            salt,
+            proof: RotationProof::to(&proof),
+        }
+    }
+
+    #[tokio::test]
+    async fn rotation_cannot_be_replayed() {
+        let params = pallas::Point::group_params();
+        let auth = Service::<pallas::Point>::new(params, "craft-test".to_string());
+        let a = SecretScalar::random(&mut OsRng);
+        let b = SecretScalar::random(&mut OsRng);
+        let request = RegisterRequest {
+            user: "peggy".to_string(),
+            y1: pallas::Point::to(&(params.g * a.expose_secret())),
+            y2: pallas::Point::to(&(params.h * a.expose_secret())),
+            salt: vec![0; SALT_LEN],
+        };
+        auth.register_user(Request::new(request)).await.unwrap();
+
+        // Rotate from a to b and back, with the same salt.
+        let a_to_b = rotate_request(&params, 0, &a, &b);
+        auth.rotate_credentials(Request::new(a_to_b.clone()))
+            .await
+            .unwrap();
+        let b_to_a = rotate_request(&params, 1, &b, &a);
+        auth.rotate_credentials(Request::new(b_to_a)).await.unwrap();
+        let salt = auth
+            .get_salt(Request::new(SaltRequest {
+                user: "peggy".to_string(),
+            }))
+            .await
+            .unwrap();
+        assert_eq!(salt.into_inner().rotations, 2);
+
+        // The account is back at a, but the recorded rotation to b is stale.
+        let status = auth
+            .rotate_credentials(Request::new(a_to_b))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::PermissionDenied);
+    }
 }